@@ -1,11 +1,14 @@
 use std::error::Error;
 use std::fmt;
 
+use crate::span::{Span, LineCol};
+
 pub type ParseResult<T> = std::result::Result<T, ParseError>;
 
 #[derive(Clone, Debug)]
 pub struct TokenInfo {
-    pub loc: (usize, usize)
+    pub span: Span,
+    pub line_col: LineCol,
 }
 
 #[derive(Debug)]
@@ -22,33 +25,304 @@ pub enum ParseError {
     ExpectedFieldIdentifierError(TokenInfo),
     DuplicateFieldIdentifierError(TokenInfo),
     MissingModelTypeError(TokenInfo),
+    ExpectedEnumVariantError(TokenInfo),
+    DuplicateEnumVariantError(TokenInfo),
+    GenericAnnotationError(TokenInfo, String),
+    MissingInverseFieldError(TokenInfo),
+    InvalidRelationError(TokenInfo, String),
     GenericError(TokenInfo)
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &*self {
-            ParseError::UnknownError => write!(f, "Uknown parsing error\n"),
-            ParseError::NoFieldsError => write!(f, "No fields present on type. \n"),
-            ParseError::DuplicateModelIdentifierError(ref info) => write!(f, "Duplicate model identifier: ({}, {})", info.loc.0, info.loc.1),
-            ParseError::MissingColonError(ref info) => write!(f, "Missing colon to indicate type on field: ({}, {})", info.loc.0, info.loc.1),
-            ParseError::MissingFieldTypeError(ref info) => write!(f, "Missing type on field: ({}, {})", info.loc.0, info.loc.1),
-            ParseError::GenericFieldTypeError(ref info) => write!(f, "Something is wrong with the type on field: ({}, {})", info.loc.0, info.loc.1),
+        match self {
+            ParseError::UnknownError => writeln!(f, "Uknown parsing error"),
+            ParseError::NoFieldsError => writeln!(f, "No fields present on type. "),
+            ParseError::DuplicateModelIdentifierError(ref info) => write!(f, "Duplicate model identifier: {}", info.line_col),
+            ParseError::MissingColonError(ref info) => write!(f, "Missing colon to indicate type on field: {}", info.line_col),
+            ParseError::MissingFieldTypeError(ref info) => write!(f, "Missing type on field: {}", info.line_col),
+            ParseError::GenericFieldTypeError(ref info) => write!(f, "Something is wrong with the type on field: {}", info.line_col),
             ParseError::GenericFieldParsingError(ref info, message) => {
                 let optional_location = if let Some(i) = info {
-                    format!(": ({}, {})", i.loc.0, i.loc.1)
+                    format!(": {}", i.line_col)
                 } else {
                     String::from(".")
                 };
                 write!(f, "Something is wrong with the type on field{}\nError message: {}", optional_location, message)
             },
-            ParseError::MissingRightBracketError(ref info) => write!(f, "Missing '}}' to close the type definition: ({}, {})", info.loc.0, info.loc.1),
-            ParseError::ExpectedFieldIdentifierError(ref info) => write!(f, "Expected a field identifier: ({}, {})", info.loc.0, info.loc.1),
-            ParseError::DuplicateFieldIdentifierError(ref info) => write!(f, "Duplicate field identifier: ({}, {})", info.loc.0, info.loc.1),
-            ParseError::MissingModelTypeError(ref info) => write!(f, "Missing model type: ({}, {})", info.loc.0, info.loc.1),
-            ParseError::GenericError(ref info) => write!(f, "Generic parsing error: ({}, {})", info.loc.0, info.loc.1)
+            ParseError::MissingRightBracketError(ref info) => write!(f, "Missing '}}' to close the type definition: {}", info.line_col),
+            ParseError::ExpectedFieldIdentifierError(ref info) => write!(f, "Expected a field identifier: {}", info.line_col),
+            ParseError::DuplicateFieldIdentifierError(ref info) => write!(f, "Duplicate field identifier: {}", info.line_col),
+            ParseError::MissingModelTypeError(ref info) => write!(f, "Missing model type: {}", info.line_col),
+            ParseError::ExpectedEnumVariantError(ref info) => write!(f, "Expected an enum variant: {}", info.line_col),
+            ParseError::DuplicateEnumVariantError(ref info) => write!(f, "Duplicate enum variant: {}", info.line_col),
+            ParseError::GenericAnnotationError(ref info, message) => write!(f, "Something is wrong with the annotation: {}\nError message: {}", info.line_col, message),
+            ParseError::MissingInverseFieldError(ref info) => write!(f, "Missing inverse field on the related type: {}", info.line_col),
+            ParseError::InvalidRelationError(ref info, message) => write!(f, "Something is wrong with the relation: {}\nError message: {}", info.line_col, message),
+            ParseError::GenericError(ref info) => write!(f, "Generic parsing error: {}", info.line_col)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl ParseError {
+    // stable error code used when rendering diagnostics
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::UnknownError => "E0001",
+            ParseError::NoFieldsError => "E0002",
+            ParseError::DuplicateFieldIdentifierError(_) => "E0003",
+            ParseError::DuplicateModelIdentifierError(_) => "E0004",
+            ParseError::MissingColonError(_) => "E0005",
+            ParseError::MissingFieldTypeError(_) => "E0006",
+            ParseError::GenericFieldTypeError(_) => "E0007",
+            ParseError::GenericFieldParsingError(_, _) => "E0008",
+            ParseError::MissingRightBracketError(_) => "E0009",
+            ParseError::ExpectedFieldIdentifierError(_) => "E0010",
+            ParseError::MissingModelTypeError(_) => "E0011",
+            ParseError::GenericError(_) => "E0012",
+            ParseError::ExpectedEnumVariantError(_) => "E0013",
+            ParseError::DuplicateEnumVariantError(_) => "E0014",
+            ParseError::GenericAnnotationError(_, _) => "E0015",
+            ParseError::MissingInverseFieldError(_) => "E0016",
+            ParseError::InvalidRelationError(_, _) => "E0017",
+        }
+    }
+
+    pub fn info(&self) -> Option<&TokenInfo> {
+        match self {
+            ParseError::UnknownError | ParseError::NoFieldsError => None,
+            ParseError::GenericFieldParsingError(info, _) => info.as_ref(),
+            ParseError::GenericAnnotationError(info, _)
+            | ParseError::InvalidRelationError(info, _) => Some(info),
+            ParseError::DuplicateModelIdentifierError(info)
+            | ParseError::MissingColonError(info)
+            | ParseError::MissingFieldTypeError(info)
+            | ParseError::GenericFieldTypeError(info)
+            | ParseError::MissingRightBracketError(info)
+            | ParseError::ExpectedFieldIdentifierError(info)
+            | ParseError::DuplicateFieldIdentifierError(info)
+            | ParseError::MissingModelTypeError(info)
+            | ParseError::ExpectedEnumVariantError(info)
+            | ParseError::DuplicateEnumVariantError(info)
+            | ParseError::MissingInverseFieldError(info)
+            | ParseError::GenericError(info) => Some(info),
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.info().map(|i| i.span)
+    }
+}
+
+pub type MigrationResult<T> = std::result::Result<T, MigrationError>;
+
+// spans point into the migration file
+#[derive(Debug)]
+pub enum MigrationError {
+    UnknownModelError(String, Span),
+    DuplicateModelError(String, Span),
+    UnknownEnumError(String, Span),
+    DuplicateEnumError(String, Span),
+    UnknownFieldError(String, String, Span),
+    DuplicateFieldError(String, String, Span),
+    MissingReferenceError(String, String, String), // model, field, missing type
+    MissingInverseFieldError(String, String, String), // model, field, missing inverse
+    InvalidRelationError(String, String, RelationError), // model, field
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::UnknownModelError(name, _) => write!(f, "Model '{}' does not exist in the schema", name),
+            MigrationError::DuplicateModelError(name, _) => write!(f, "Type '{}' already exists in the schema", name),
+            MigrationError::UnknownEnumError(name, _) => write!(f, "Enum '{}' does not exist in the schema", name),
+            MigrationError::DuplicateEnumError(name, _) => write!(f, "Type '{}' already exists in the schema", name),
+            MigrationError::UnknownFieldError(model, field, _) => write!(f, "Field '{}' does not exist on model '{}'", field, model),
+            MigrationError::DuplicateFieldError(model, field, _) => write!(f, "Field '{}' already exists on model '{}'", field, model),
+            MigrationError::MissingReferenceError(model, field, missing) => write!(f, "Field '{}.{}' references '{}' which does not exist after the migration", model, field, missing),
+            MigrationError::MissingInverseFieldError(model, field, inverse) => write!(f, "Relation '{}.{}' points at inverse field '{}' which does not exist after the migration", model, field, inverse),
+            MigrationError::InvalidRelationError(model, field, e) => write!(f, "Relation '{}.{}' is invalid after the migration: {}", model, field, e),
+        }
+    }
+}
+
+impl MigrationError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            MigrationError::UnknownModelError(_, span)
+            | MigrationError::DuplicateModelError(_, span)
+            | MigrationError::UnknownEnumError(_, span)
+            | MigrationError::DuplicateEnumError(_, span)
+            | MigrationError::UnknownFieldError(_, _, span)
+            | MigrationError::DuplicateFieldError(_, _, span) => Some(*span),
+            MigrationError::MissingReferenceError(_, _, _)
+            | MigrationError::MissingInverseFieldError(_, _, _)
+            | MigrationError::InvalidRelationError(_, _, _) => None,
+        }
+    }
+}
+
+impl Error for MigrationError {}
+
+// what `Root::check_relation` found wrong with a relation, the parser and migrations report it in their own way
+#[derive(Clone, Debug, PartialEq)]
+pub enum RelationError {
+    UnknownTypeError,
+    ScalarTypeError,
+    EnumTypeError,
+    MissingInverseError,
+    InverseTypeError(String, String), // model the inverse should reference, what it references instead
+    InverseRelationError(String, String), // field the inverse points back at, the field it should point at
+}
+
+impl fmt::Display for RelationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelationError::UnknownTypeError => write!(f, "Relations have to point at a declared model."),
+            RelationError::ScalarTypeError => write!(f, "Relations can only point at a model."),
+            RelationError::EnumTypeError => write!(f, "Relations can only point at a model, not an enum."),
+            RelationError::MissingInverseError => write!(f, "The inverse field does not exist on the related model."),
+            RelationError::InverseTypeError(expected, actual) => write!(f, "Inverse field must reference '{}' but it references '{}'.", expected, actual),
+            RelationError::InverseRelationError(actual, expected) => write!(f, "Inverse field points back at '{}' instead of '{}'.", actual, expected),
+        }
+    }
+}
+
+impl Error for RelationError {}
+
+pub type ImportResult<T> = std::result::Result<T, ImportError>;
+
+// spans point into the imported document
+#[derive(Debug)]
+pub enum ImportError {
+    SyntaxError(String, Span), // what was expected
+    UnsupportedError(String, Span), // the construct odetto can't represent
+    UnknownTypeError(String, Span),
+    DuplicateTypeError(String, Span),
+    DuplicateFieldError(String, String, Span),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::SyntaxError(expected, _) => write!(f, "Expected {}", expected),
+            ImportError::UnsupportedError(construct, _) => write!(f, "{} can't be represented in odetto yet", construct),
+            ImportError::UnknownTypeError(name, _) => write!(f, "Type '{}' is not declared in the document", name),
+            ImportError::DuplicateTypeError(name, _) => write!(f, "Type '{}' is declared more than once", name),
+            ImportError::DuplicateFieldError(model, field, _) => write!(f, "Field '{}' is declared more than once on '{}'", field, model),
+        }
+    }
+}
+
+impl ImportError {
+    pub fn span(&self) -> Span {
+        match self {
+            ImportError::SyntaxError(_, span)
+            | ImportError::UnsupportedError(_, span)
+            | ImportError::UnknownTypeError(_, span)
+            | ImportError::DuplicateTypeError(_, span)
+            | ImportError::DuplicateFieldError(_, _, span) => *span,
+        }
+    }
+}
+
+impl Error for ImportError {}
+
+pub type RenameResult<T> = std::result::Result<T, RenameError>;
+
+// spans point at the existing declaration the new name would collide with
+#[derive(Debug)]
+pub enum RenameError {
+    SchemaError(ParseError), // renames only work on schemas that parse
+    InvalidNameError(String),
+    UnknownTypeError(String),
+    UnknownFieldError(String, String),
+    DuplicateTypeError(String, Span),
+    DuplicateFieldError(String, String, Span),
+    ScalarNameError(String), // a custom scalar of the project
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::SchemaError(e) => write!(f, "The schema has to parse before renaming: {}", e),
+            RenameError::InvalidNameError(name) => write!(f, "'{}' is not a valid name", name),
+            RenameError::UnknownTypeError(name) => write!(f, "Type '{}' does not exist in the schema", name),
+            RenameError::UnknownFieldError(model, field) => write!(f, "Field '{}' does not exist on model '{}'", field, model),
+            RenameError::DuplicateTypeError(name, _) => write!(f, "Type '{}' already exists in the schema", name),
+            RenameError::DuplicateFieldError(model, field, _) => write!(f, "Field '{}' already exists on model '{}'", field, model),
+            RenameError::ScalarNameError(name) => write!(f, "'{}' is already a scalar of the project", name),
+        }
+    }
+}
+
+impl RenameError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            RenameError::SchemaError(e) => e.span(),
+            RenameError::DuplicateTypeError(_, span)
+            | RenameError::DuplicateFieldError(_, _, span) => Some(*span),
+            RenameError::InvalidNameError(_)
+            | RenameError::UnknownTypeError(_)
+            | RenameError::UnknownFieldError(_, _)
+            | RenameError::ScalarNameError(_) => None,
+        }
+    }
+}
+
+impl Error for RenameError {}
+
+pub type GenerateResult<T> = std::result::Result<T, GenerateError>;
+
+#[derive(Debug)]
+pub enum GenerateError {
+    UnknownTargetError(String),
+    UnknownOptionError(String, String), // target, option
+    MissingOptionError(String, String), // target, option
+    InvalidOptionError(String, String, String), // option, value, what was expected
+    GeneratorError(String, String), // target, message from a generator that failed on its own
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::UnknownTargetError(target) => write!(f, "There is no generator for target '{}'", target),
+            GenerateError::UnknownOptionError(target, option) => write!(f, "Target '{}' has no option '{}'", target, option),
+            GenerateError::MissingOptionError(target, option) => write!(f, "Target '{}' needs the option '{}'", target, option),
+            GenerateError::InvalidOptionError(option, value, expected) => write!(f, "Option '{}' expects {}, got '{}'", option, expected, value),
+            GenerateError::GeneratorError(target, message) => write!(f, "Generator '{}' failed: {}", target, message),
+        }
+    }
+}
+
+impl Error for GenerateError {}
+
+pub type ConfigResult<T> = std::result::Result<T, ConfigError>;
+
+// paths are the ones from the config file, relative to its directory
+#[derive(Debug)]
+pub enum ConfigError {
+    ReadError(String, String), // file, io error
+    SyntaxError(String, String), // config file, message from the yaml or toml parser
+    UnknownLintError(String),
+    InvalidScalarError(String),
+    PatternError(String, String), // glob, what is wrong with it
+    NoSchemaError(String), // glob that matched no file
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::ReadError(file, e) => write!(f, "Could not read {}: {}", file, e),
+            ConfigError::SyntaxError(file, e) => write!(f, "Invalid config in {}: {}", file, e),
+            ConfigError::UnknownLintError(rule) => write!(f, "There is no lint rule called '{}'", rule),
+            ConfigError::InvalidScalarError(name) => write!(f, "'{}' can't be used as a custom scalar", name),
+            ConfigError::PatternError(pattern, e) => write!(f, "Invalid schema pattern '{}': {}", pattern, e),
+            ConfigError::NoSchemaError(pattern) => write!(f, "Schema pattern '{}' matched no files", pattern),
         }
     }
 }
 
-impl Error for ParseError {}
\ No newline at end of file
+impl Error for ConfigError {}
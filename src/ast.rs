@@ -1,34 +1,190 @@
 use std::fmt;
-// @todo will need `transition` types for parsing migration files
 
-#[derive(Clone, Debug)]
+use crate::{
+    _helpers::RelationError,
+    span::Span,
+};
+
+// with the `serde` feature the schema serializes to a stable shape, e.g. as json:
+//
+//   root:        { "types": [model], "enums": [enum] }
+//   model:       { "name", "fields": [field], "doc", "annotations": [annotation], "span", "name_span" }
+//   enum:        { "name", "variants": [{ "name", "doc", "span" }], "doc", "annotations", "span", "name_span" }
+//   field:       { "name", "field_type", "type_type", "required", "doc", "annotations", "relation", "span", "name_span" }
+//   field_type:  { "kind": "scalar" | "identifier", "name", "span" }, identifiers name a model or an enum
+//   type_type:   "basic" | "array" | "required_array"
+//   relation:    { "inverse", "inverse_span", "cardinality", "span" }
+//   cardinality: "one_to_one" | "one_to_many" | "many_to_one" | "many_to_many"
+//   annotation:  { "name", "args": [{ "name", "value" }], "span" }
+//   value:       { "kind": "int" | "float" | "string" | "bool" | "identifier" | "list", "value" }
+//   span:        { "start", "end" }, byte offsets into the source with `end` exclusive
+//
+// every key is always present, missing docs, relations and cardinalities are `null`.
+// keys may be added but existing ones keep their name and meaning
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default)]
 pub struct Root {
     pub types: Vec<ModelTypeDef>,
+    pub enums: Vec<EnumDef>,
     // Identifier_reference: Vec<String> // possibly just put this on the parser but might be handy? Could also possibly use Box as a pointer to an tree node? 
 }
 
 impl Root {
     pub fn new() -> Root {
         Root {
-            types: Vec::new()
+            types: Vec::new(),
+            enums: Vec::new(),
         }
     }
+
+    pub fn model(&self, name: &str) -> Option<&ModelTypeDef> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    pub fn enum_def(&self, name: &str) -> Option<&EnumDef> {
+        self.enums.iter().find(|e| e.name == name)
+    }
+
+    // names of the models and then the enums
+    pub fn type_names(&self) -> impl Iterator<Item = &String> {
+        self.types.iter().map(|m| &m.name).chain(self.enums.iter().map(|e| &e.name))
+    }
+
+    // models and enums share one namespace
+    pub fn is_declared(&self, name: &str) -> bool {
+        self.model(name).is_some() || self.enum_def(name).is_some()
+    }
+
+    // the field on the other side of a relation declared with `->`
+    pub fn relation_inverse(&self, field: &FieldDef) -> Option<&FieldDef> {
+        let relation = field.relation.as_ref()?;
+        self.model(field.field_type.name())?.fields.iter().find(|f| f.name == relation.inverse)
+    }
+
+    // the inverse of a relation declared on `model`, which has to point back at it.
+    // parsed schemas and migrated ones are held to the same rules, `Ok(None)` when `field` has no relation
+    pub fn check_relation(&self, model: &ModelTypeDef, field: &FieldDef) -> Result<Option<&FieldDef>, RelationError> {
+        let relation = match &field.relation {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+
+        let target = match &field.field_type {
+            FieldType::Scalar(_, _) => return Err(RelationError::ScalarTypeError),
+            FieldType::Identfier(name, _) => match self.model(name) {
+                Some(t) => t,
+                None if self.enum_def(name).is_some() => return Err(RelationError::EnumTypeError),
+                None => return Err(RelationError::UnknownTypeError),
+            },
+        };
+
+        let inverse = target.fields.iter().find(|i| i.name == relation.inverse).ok_or(RelationError::MissingInverseError)?;
+
+        if inverse.field_type.name() != model.name {
+            return Err(RelationError::InverseTypeError(model.name.clone(), inverse.field_type.name().to_string()));
+        }
+        if let Some(back) = &inverse.relation {
+            if back.inverse != field.name {
+                return Err(RelationError::InverseRelationError(back.inverse.clone(), field.name.clone()));
+            }
+        }
+
+        Ok(Some(inverse))
+    }
 }
 
-// @todo remove default once annotations are complete
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug)]
 pub struct ModelTypeDef {
     pub name: String,
     pub fields: Vec<FieldDef>,
-    pub annotation: Option<String>,
+    pub doc: Option<String>,
+    pub annotations: Vec<Annotation>,
+    pub span: Span, // from the `type` keyword to the closing curly bracket
+    pub name_span: Span,
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct EnumDef {
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+    pub doc: Option<String>,
+    pub annotations: Vec<Annotation>,
+    pub span: Span, // from the `enum` keyword to the closing curly bracket
+    pub name_span: Span,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct EnumVariant {
+    pub name: String,
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "FieldTypeRepr", from = "FieldTypeRepr"))]
 #[derive(Clone, Debug)]
 pub enum FieldType {
-    Scalar(String),
-    Identfier(String)
+    Scalar(String, Span),
+    Identfier(String, Span) // a model or an enum, resolved by the parser
+}
+
+// `Identfier(name, span)` can't be tagged by serde directly
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FieldTypeRepr {
+    kind: FieldTypeKind,
+    name: String,
+    span: Span,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum FieldTypeKind {
+    Scalar,
+    Identifier,
+}
+
+#[cfg(feature = "serde")]
+impl From<FieldType> for FieldTypeRepr {
+    fn from(field_type: FieldType) -> FieldTypeRepr {
+        match field_type {
+            FieldType::Scalar(name, span) => FieldTypeRepr { kind: FieldTypeKind::Scalar, name, span },
+            FieldType::Identfier(name, span) => FieldTypeRepr { kind: FieldTypeKind::Identifier, name, span },
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<FieldTypeRepr> for FieldType {
+    fn from(repr: FieldTypeRepr) -> FieldType {
+        match repr.kind {
+            FieldTypeKind::Scalar => FieldType::Scalar(repr.name, repr.span),
+            FieldTypeKind::Identifier => FieldType::Identfier(repr.name, repr.span),
+        }
+    }
 }
 
+impl FieldType {
+    pub fn name(&self) -> &str {
+        match self {
+            FieldType::Scalar(name, _) | FieldType::Identfier(name, _) => name,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            FieldType::Scalar(_, span) | FieldType::Identfier(_, span) => *span,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 #[derive(Clone, Debug, PartialEq)]
 pub enum FieldTypeType {
     Basic, // Int, String, Comment etc -> needs better name
@@ -36,30 +192,236 @@ pub enum FieldTypeType {
     RequiredArray // [String!] not [String]! - exclamation inside not outside
 }
 
-// @todo remove default once annotations are complete
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug)]
 pub struct FieldDef {
     pub name: String,
     pub field_type: FieldType, // @todo could possibly use types from interpetors? Or leave as string and let the interpretor handle?
     pub type_type: FieldTypeType,
     pub required: bool,
-    pub annotation: Option<String>,
-    // pub location: (usize, usize),
+    pub doc: Option<String>,
+    pub annotations: Vec<Annotation>,
+    pub relation: Option<Relation>,
+    pub span: Span, // from the field name to the end of the field, including `!` and annotations
+    pub name_span: Span,
+}
+
+// `author: User -> posts`, the arrow names the field on the other side
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Relation {
+    pub inverse: String,
+    pub inverse_span: Span,
+    pub cardinality: Option<Cardinality>, // filled in by the parser once both sides are known
+    pub span: Span,
+}
+
+// seen from the model declaring the field, `Post.author: User -> posts` is many posts to one user
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cardinality {
+    OneToOne,
+    OneToMany,
+    ManyToOne,
+    ManyToMany,
+}
+
+impl PartialEq for Relation {
+    fn eq(&self, other: &Relation) -> bool {
+        self.inverse == other.inverse && self.cardinality == other.cardinality
+    }
+}
+
+impl Cardinality {
+    pub fn between(field: &FieldDef, inverse: &FieldDef) -> Cardinality {
+        match (field.type_type != FieldTypeType::Basic, inverse.type_type != FieldTypeType::Basic) {
+            (false, false) => Cardinality::OneToOne,
+            (true, false) => Cardinality::OneToMany,
+            (false, true) => Cardinality::ManyToOne,
+            (true, true) => Cardinality::ManyToMany,
+        }
+    }
+}
+
+// a parsed migration file, applied in order on top of an existing schema
+#[derive(Clone, Debug, Default)]
+pub struct Migration {
+    pub transitions: Vec<Transition>,
+}
+
+impl Migration {
+    pub fn new() -> Migration {
+        Migration {
+            transitions: Vec::new()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Transition {
+    AddModel(ModelTypeDef), // +type Name { ... }
+    RemoveModel { name: String, span: Span }, // -type Name
+    ChangeModel(ModelTransition), // type Name { ... } without a marker
+    AddEnum(EnumDef),
+    RemoveEnum { name: String, span: Span },
+    ReplaceEnum(EnumDef), // unmarked enums replace the existing variants
+}
+
+#[derive(Clone, Debug)]
+pub struct ModelTransition {
+    pub name: String,
+    pub rename_from: Option<String>, // from `@rename(from: "Old")`
+    pub doc: Option<String>, // replaces the existing doc string when present
+    pub annotations: Option<Vec<Annotation>>, // replaces the existing annotations when present
+    pub clear_doc: bool, // from `@clear(doc: true)`, an absent doc string keeps the existing one
+    pub clear_annotations: bool, // from `@clear(annotations: true)`
+    pub fields: Vec<FieldTransition>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum FieldTransition {
+    Add(FieldDef), // +name: Type
+    Remove { name: String, span: Span }, // -name
+    Change { field: FieldDef, rename_from: Option<String> }, // name: Type replaces the definition
+}
+
+// `@name(arg: value, ...)` directive on a model, enum or field
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Annotation {
+    pub name: String,
+    pub args: Vec<AnnotationArg>,
+    pub span: Span,
+}
+
+// spans are only positional, two annotations written in different files are still equal
+impl PartialEq for Annotation {
+    fn eq(&self, other: &Annotation) -> bool {
+        self.name == other.name && self.args == other.args
+    }
+}
+
+impl Annotation {
+    pub fn arg(&self, name: &str) -> Option<&AnnotationValue> {
+        self.args.iter().find(|a| a.name == name).map(|a| &a.value)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotationArg {
+    pub name: String,
+    pub value: AnnotationValue,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value", rename_all = "snake_case"))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnnotationValue {
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bool(bool),
+    Identifier(String), // bare words such as enum variants
+    List(Vec<AnnotationValue>),
+}
+
+// finds a field by name, e.g. `field(&model.fields, "id")`
+pub fn field<'a>(fields: &'a [FieldDef], name: &str) -> Option<&'a FieldDef> {
+    fields.iter().find(|f| f.name == name)
+}
+
+// finds an annotation by name, e.g. `annotation(&field.annotations, "primary")`
+pub fn annotation<'a>(annotations: &'a [Annotation], name: &str) -> Option<&'a Annotation> {
+    annotations.iter().find(|a| a.name == name)
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", self.name)?;
+        if !self.args.is_empty() {
+            let args: Vec<String> = self.args.iter().map(|a| format!("{}: {}", a.name, a.value)).collect();
+            write!(f, "({})", args.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AnnotationValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnnotationValue::Int(i) => write!(f, "{}", i),
+            AnnotationValue::Float(n) => write!(f, "{:?}", n),
+            AnnotationValue::String(s) => write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t")),
+            AnnotationValue::Bool(b) => write!(f, "{}", b),
+            AnnotationValue::Identifier(i) => write!(f, "{}", i),
+            AnnotationValue::List(values) => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(f, "[{}]", values.join(", "))
+            },
+        }
+    }
+}
+
+impl FieldDef {
+    // the type as written in `.odet` source, e.g. `[String!]!`
+    pub fn type_string(&self) -> String {
+        let name = self.field_type.name();
+        let required = if self.required { "!" } else { "" };
+
+        match self.type_type {
+            FieldTypeType::Basic => format!("{}{}", name, required),
+            FieldTypeType::Array => format!("[{}]{}", name, required),
+            FieldTypeType::RequiredArray => format!("[{}!]{}", name, required),
+        }
+    }
+}
+
+// `name: Type -> inverse @annotations` without the doc string
+impl fmt::Display for FieldDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.type_string())?;
+        if let Some(r) = &self.relation {
+            write!(f, " -> {}", r.inverse)?;
+        }
+        write!(f, "{}", annotations_suffix(&self.annotations))
+    }
+}
+
+// ` @a @b(x: 1)`, with a leading space so it can follow a name
+pub(crate) fn annotations_suffix(annotations: &[Annotation]) -> String {
+    annotations.iter().map(|a| format!(" {}", a)).collect()
 }
 
 impl fmt::Display for Root {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let vec = &self.types;
 
-        write!(f, "[\n")?;
-        for (_, m) in vec.iter().enumerate() {
-            write!(f, "\tModel: {}{} [\n", m.name, if let Some(a) = &m.annotation { format!(" '{}'", a) } else { String::new() })?;
-            for (_, d) in m.fields.iter().enumerate() {
-                write!(f, "\t\t{} : {:?}{} - {:?}{}\n", d.name, d.field_type, if d.required == true { "!" } else { "" }, d.type_type, if let Some(a) = &d.annotation { format!(" '{}'", a) } else { String::new() })?;
+        writeln!(f, "[")?;
+        for m in vec.iter() {
+            writeln!(f, "\tModel: {}{}{} [", m.name, annotations_suffix(&m.annotations), if let Some(a) = &m.doc { format!(" '{}'", a) } else { String::new() })?;
+            for d in m.fields.iter() {
+                let field_type = match &d.field_type {
+                    FieldType::Scalar(name, _) => format!("Scalar({:?})", name),
+                    FieldType::Identfier(name, _) => format!("Identfier({:?})", name),
+                };
+                let relation = match &d.relation {
+                    Some(r) => format!(" -> {}{}", r.inverse, if let Some(c) = r.cardinality { format!(" ({:?})", c) } else { String::new() }),
+                    None => String::new(),
+                };
+                writeln!(f, "\t\t{} : {}{}{} - {:?}{}{}", d.name, field_type, if d.required { "!" } else { "" }, relation, d.type_type, annotations_suffix(&d.annotations), if let Some(a) = &d.doc { format!(" '{}'", a) } else { String::new() })?;
             }
-            write!(f, "\t]\n")?;
+            writeln!(f, "\t]")?;
         }
-        write!(f, "]\n")
-        
+        for e in self.enums.iter() {
+            writeln!(f, "\tEnum: {}{}{} [", e.name, annotations_suffix(&e.annotations), if let Some(a) = &e.doc { format!(" '{}'", a) } else { String::new() })?;
+            for v in e.variants.iter() {
+                writeln!(f, "\t\t{}{}", v.name, if let Some(a) = &v.doc { format!(" '{}'", a) } else { String::new() })?;
+            }
+            writeln!(f, "\t]")?;
+        }
+        writeln!(f, "]")
     }
-}
\ No newline at end of file
+}
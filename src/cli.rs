@@ -0,0 +1,619 @@
+use std::{fs, io::{self, Read}, path::{Path, Component}};
+
+use crate::{
+    lexer::{self, Tokens},
+    parser::{self, ParseOptions},
+    ast::Root,
+    config::{self, Config, Sources},
+    lint,
+    diagnostics::Diagnostic,
+    diff,
+    compat::{self, Compatibility, Consumer},
+    format,
+    _helpers::GenerateResult,
+    codegen::{GeneratedFile, GeneratorOptions, Registry},
+};
+#[cfg(feature = "serde")]
+use crate::codegen::plugin::ExternalGenerator;
+
+fn usage(registry: &Registry) -> String {
+    format!("usage: odetto-cli <command> [options]
+
+commands:
+    check [file.odet]...                      report every error and warning, exits with 1 on errors
+    tokens [file.odet]                        print the tokens of a schema
+    ast [file.odet] [--format debug|json|yaml]
+                                              print the parsed schema, json and yaml need the `serde` feature
+    fmt [--check] <file.odet>...              format schemas in place
+    generate --target <target> [--option <key=value>]... [--out <dir>] [file.odet]
+                                              generate code, targets: {} or any
+                                              `odetto-gen-<target>` on PATH
+    generate                                  run every target of the project config
+    check-compat [--consumer <consumer>] <old.odet> <new.odet>
+                                              classify the changes between two schemas
+
+a file called `-` or a missing file is read from stdin.
+the nearest {} adds custom scalars and lint rules, `check` without files checks its schema.
+exits with 2 when the arguments are wrong or a schema can't be read or parsed", registry.names().join(", "), config::FILE_NAMES.join(" or "))
+}
+
+// runs `odetto-cli` with the arguments after the binary name and returns the exit code.
+// a binary linking its own generators passes a registry with them added
+pub fn run(args: &[String], registry: &Registry) -> i32 {
+    let rest = args.get(1..).unwrap_or(&[]);
+
+    match args.first().map(|a| a.as_str()) {
+        Some("check") => check(rest),
+        Some("tokens") => tokens(rest),
+        Some("ast") => ast(rest),
+        Some("fmt") => fmt(rest),
+        Some("generate") => generate(rest, registry),
+        Some("check-compat") => check_compat(rest),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", usage(registry));
+            0
+        },
+        Some(command) => {
+            eprintln!("unknown command `{}`\n\n{}", command, usage(registry));
+            2
+        },
+        None => {
+            eprintln!("{}", usage(registry));
+            2
+        },
+    }
+}
+
+// every file is checked even after a failing one, exits with 1 when any of them has errors.
+// the lint rules enabled in the project config are run on the files that parse
+fn check(args: &[String]) -> i32 {
+    let project = match project() {
+        Ok(p) => p,
+        Err(()) => return 2,
+    };
+    // without files the project's schema is checked, or stdin outside of a project
+    let (config, files) = match (args.is_empty(), project) {
+        (true, Some(config)) => return check_project(&config),
+        (true, None) => (Config::default(), vec![String::from("-")]),
+        (false, project) => (project.unwrap_or_default(), args.to_vec()),
+    };
+
+    let mut code = 0;
+
+    for file_name in files.iter() {
+        let contents = match read_input(file_name) {
+            Some(c) => c,
+            None => {
+                code = 2;
+                continue;
+            },
+        };
+
+        let tokens = lexer::Lexer::new(contents.as_str()).run();
+        let diagnostics = check_schema(&tokens, &config);
+
+        for d in diagnostics.iter() {
+            eprint!("{}", d.render(file_name, tokens.line_index()));
+        }
+        if diagnostics.iter().any(|d| d.is_error()) {
+            code = code.max(1);
+        }
+    }
+
+    code
+}
+
+// the project's schema files are checked together, they can refer to each other
+fn check_project(config: &Config) -> i32 {
+    let sources = match read_project_schema(config) {
+        Some(s) => s,
+        None => return 2,
+    };
+    let tokens = lexer::Lexer::new(sources.text.as_str()).run();
+    let diagnostics = check_schema(&tokens, config);
+
+    for d in diagnostics.iter() {
+        eprint!("{}", sources.render(d));
+    }
+
+    if diagnostics.iter().any(|d| d.is_error()) { 1 } else { 0 }
+}
+
+fn check_schema(tokens: &Tokens, config: &Config) -> Vec<Diagnostic> {
+    let (root, mut diagnostics) = parser::Parser::with_options(tokens, config.parse_options()).parse_recovering();
+
+    if !diagnostics.iter().any(|d| d.is_error()) {
+        diagnostics.extend(lint::lint(&root, &config.lint_rules()));
+    }
+
+    diagnostics
+}
+
+fn tokens(args: &[String]) -> i32 {
+    let file_name = match single_file(args, "usage: odetto-cli tokens [file.odet]") {
+        Some(f) => f,
+        None => return 2,
+    };
+    let contents = match read_input(file_name) {
+        Some(c) => c,
+        None => return 2,
+    };
+
+    print!("{}", lexer::Lexer::new(contents.as_str()).run());
+    0
+}
+
+fn ast(args: &[String]) -> i32 {
+    const AST_USAGE: &str = "usage: odetto-cli ast [file.odet] [--format debug|json|yaml]";
+
+    let mut output = "debug";
+    let mut files = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--format" {
+            match args.next().map(|f| f.as_str()) {
+                Some(f @ "debug") | Some(f @ "json") | Some(f @ "yaml") => output = f,
+                _ => {
+                    eprintln!("--format expects debug, json or yaml");
+                    return 2;
+                },
+            }
+        } else {
+            files.push(arg.clone());
+        }
+    }
+
+    let file_name = match single_file(&files, AST_USAGE) {
+        Some(f) => f,
+        None => return 2,
+    };
+    let parse_options = match project_parse_options() {
+        Some(o) => o,
+        None => return 2,
+    };
+    let root = match load_schema(file_name, &parse_options) {
+        Some(root) => root,
+        None => return 2,
+    };
+
+    match output {
+        "json" | "yaml" => print_serialized(&root, output),
+        _ => {
+            println!("{:#?}", root);
+            0
+        },
+    }
+}
+
+// the shape is documented in `ast.rs`
+#[cfg(feature = "serde")]
+fn print_serialized(root: &Root, output: &str) -> i32 {
+    let serialized = match output {
+        "yaml" => serde_yaml::to_string(root).map_err(|e| e.to_string()),
+        _ => serde_json::to_string_pretty(root).map(|json| json + "\n").map_err(|e| e.to_string()),
+    };
+
+    match serialized {
+        Ok(s) => {
+            print!("{}", s);
+            0
+        },
+        Err(e) => {
+            eprintln!("could not serialize the schema: {}", e);
+            2
+        },
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_serialized(_: &Root, output: &str) -> i32 {
+    eprintln!("odetto-cli was built without the `serde` feature, {} output is not available", output);
+    2
+}
+
+// files are written to `--out`, or printed one after the other when it's missing.
+// targets that aren't registered are looked up as `odetto-gen-<target>` executables on PATH.
+// without arguments every target of the project config is run
+fn generate(args: &[String], registry: &Registry) -> i32 {
+    let generate_usage = format!(
+        "usage: odetto-cli generate --target {} [--option <key=value>]... [--out <dir>] [file.odet]",
+        registry.names().join("|"),
+    );
+
+    if args.is_empty() {
+        return match project() {
+            Ok(Some(config)) => generate_project(&config, registry),
+            Ok(None) => {
+                eprintln!("{}\nwithout arguments the targets of the project config are run, there is none here", generate_usage);
+                2
+            },
+            Err(()) => 2,
+        };
+    }
+
+    let mut target = None;
+    let mut out = None;
+    let mut options = GeneratorOptions::new();
+    let mut files = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--target" | "--out" | "--option" => {
+                let value = match args.next() {
+                    Some(v) => v.clone(),
+                    None => {
+                        eprintln!("{} expects a value\n{}", arg, generate_usage);
+                        return 2;
+                    },
+                };
+                match arg.as_str() {
+                    "--target" => target = Some(value),
+                    "--out" => out = Some(value),
+                    _ => match value.split_once('=') {
+                        Some((key, value)) => {
+                            options.insert(key.to_string(), value.to_string());
+                        },
+                        None => {
+                            eprintln!("--option expects key=value, got `{}`", value);
+                            return 2;
+                        },
+                    },
+                }
+            },
+            _ => files.push(arg.clone()),
+        }
+    }
+
+    let target = match target {
+        Some(t) => t,
+        None => {
+            eprintln!("{}", generate_usage);
+            return 2;
+        },
+    };
+    let generator = match resolve(registry, &target) {
+        Some(generator) => generator,
+        None => {
+            eprintln!("unknown target `{}`, there is no generator or `odetto-gen-{}` on PATH\n{}", target, target, generate_usage);
+            return 2;
+        },
+    };
+    let file_name = match single_file(&files, &generate_usage) {
+        Some(f) => f,
+        None => return 2,
+    };
+    let parse_options = match project_parse_options() {
+        Some(o) => o,
+        None => return 2,
+    };
+    let (root, tokens) = match load_schema_with_tokens(file_name, &parse_options) {
+        Some(loaded) => loaded,
+        None => return 2,
+    };
+
+    let generated = match run_target(&generator, &root, &options, &|d| d.render(file_name, tokens.line_index())) {
+        Ok(files) => files,
+        Err(code) => return code,
+    };
+
+    match out {
+        Some(out) => write_files(&target, &generated, Path::new(&out)),
+        None => {
+            for file in generated.iter() {
+                print!("{}", file.contents);
+            }
+            0
+        },
+    }
+}
+
+// the targets run in order and the first one failing stops the rest.
+// unknown targets and schema errors are reported before anything is written
+fn generate_project(config: &Config, registry: &Registry) -> i32 {
+    if config.targets.is_empty() {
+        eprintln!("the project config has no targets to generate");
+        return 2;
+    }
+
+    let mut generators = Vec::new();
+    for target in config.targets.iter() {
+        match resolve(registry, &target.target) {
+            Some(generator) => generators.push(generator),
+            None => {
+                eprintln!("unknown target `{}`, there is no generator or `odetto-gen-{}` on PATH", target.target, target.target);
+                return 2;
+            },
+        }
+    }
+
+    let sources = match read_project_schema(config) {
+        Some(s) => s,
+        None => return 2,
+    };
+    let tokens = lexer::Lexer::new(sources.text.as_str()).run();
+    let root = match parser::Parser::with_options(&tokens, config.parse_options()).parse() {
+        Ok(root) => root,
+        Err(e) => {
+            eprint!("{}", sources.render(&Diagnostic::from_parse_error(&e, tokens.line_index())));
+            return 2;
+        },
+    };
+
+    for (target, generator) in config.targets.iter().zip(generators) {
+        let generated = match run_target(&generator, &root, &target.options(), &|d| sources.render(d)) {
+            Ok(files) => files,
+            Err(code) => return code,
+        };
+
+        let out = config.out_dir(target);
+        let code = write_files(&target.target, &generated, &out);
+        if code != 0 {
+            return code;
+        }
+        println!("{}: {} file{} written to {}", target.target, generated.len(), if generated.len() == 1 { "" } else { "s" }, out.display());
+    }
+
+    0
+}
+
+// a registered generator or one found on PATH, the diagnostics of plugins are returned next to the files
+type Target<'a> = Box<dyn Fn(&Root, &GeneratorOptions) -> GenerateResult<(Vec<Diagnostic>, Vec<GeneratedFile>)> + 'a>;
+
+fn resolve<'a>(registry: &'a Registry, target: &str) -> Option<Target<'a>> {
+    if registry.get(target).is_none() {
+        return external_generator(target);
+    }
+
+    let target = target.to_string();
+    Some(Box::new(move |root, options| registry.generate(&target, root, options).map(|files| (Vec::new(), files))))
+}
+
+#[cfg(feature = "serde")]
+fn external_generator(target: &str) -> Option<Target<'static>> {
+    let generator = ExternalGenerator::find(target)?;
+
+    Some(Box::new(move |root, options| {
+        let response = generator.run(root, options)?;
+        Ok((response.diagnostics(&generator.name), response.files))
+    }))
+}
+
+// the plugin protocol is json, without serde only registered generators can be used
+#[cfg(not(feature = "serde"))]
+fn external_generator(_: &str) -> Option<Target<'static>> {
+    None
+}
+
+// prints the diagnostics with `render`, errors exit with the code to return.
+// nothing is written when a plugin reports errors
+fn run_target(generator: &Target, root: &Root, options: &GeneratorOptions, render: &dyn Fn(&Diagnostic) -> String) -> Result<Vec<GeneratedFile>, i32> {
+    match generator(root, options) {
+        Ok((diagnostics, files)) => {
+            for d in diagnostics.iter() {
+                eprint!("{}", render(d));
+            }
+            if diagnostics.iter().any(|d| d.is_error()) {
+                return Err(1);
+            }
+            Ok(files)
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            Err(2)
+        },
+    }
+}
+
+fn write_files(target: &str, files: &[GeneratedFile], out: &Path) -> i32 {
+    for file in files.iter() {
+        // generators from other crates shouldn't be able to write outside of `--out`
+        if !Path::new(&file.path).components().all(|c| matches!(c, Component::Normal(_))) {
+            eprintln!("generator `{}` produced the path {} outside of the output directory", target, file.path);
+            return 2;
+        }
+
+        let path = out.join(&file.path);
+        let written = path.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::write(&path, &file.contents));
+        if let Err(e) = written {
+            eprintln!("could not write {}: {}", path.display(), e);
+            return 2;
+        }
+    }
+
+    0
+}
+
+// exits with 1 on breaking changes and 2 when the schemas can't be read
+fn check_compat(args: &[String]) -> i32 {
+    let mut consumer = Consumer::All;
+    let mut files = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--consumer" {
+            match args.next().and_then(|c| Consumer::from_name(c)) {
+                Some(c) => consumer = c,
+                None => {
+                    eprintln!("--consumer expects one of reader, writer, database or all");
+                    return 2;
+                },
+            }
+        } else {
+            files.push(arg);
+        }
+    }
+
+    if files.len() != 2 {
+        eprintln!("usage: odetto-cli check-compat [--consumer reader|writer|database|all] <old.odet> <new.odet>");
+        return 2;
+    }
+
+    let parse_options = match project_parse_options() {
+        Some(o) => o,
+        None => return 2,
+    };
+    let (old, new) = match (load_schema(files[0], &parse_options), load_schema(files[1], &parse_options)) {
+        (Some(old), Some(new)) => (old, new),
+        _ => return 2,
+    };
+
+    let report = compat::classify(&old, &diff::diff(&old, &new), consumer);
+    for finding in report.findings.iter() {
+        println!("{}", finding);
+    }
+    println!(
+        "{} breaking, {} risky, {} safe",
+        report.count(Compatibility::Breaking),
+        report.count(Compatibility::Risky),
+        report.count(Compatibility::Safe),
+    );
+
+    if report.is_breaking() { 1 } else { 0 }
+}
+
+// rewrites the files in place, `--check` only lists the ones that aren't formatted and exits with 1.
+// files that don't parse are left alone and exit with 2, stdin is formatted to stdout
+fn fmt(args: &[String]) -> i32 {
+    let check = args.iter().any(|a| a == "--check");
+    let files: Vec<&String> = args.iter().filter(|a| *a != "--check").collect();
+
+    if files.is_empty() {
+        eprintln!("usage: odetto-cli fmt [--check] <file.odet>...");
+        return 2;
+    }
+    let parse_options = match project_parse_options() {
+        Some(o) => o,
+        None => return 2,
+    };
+
+    let mut code = 0;
+
+    for file_name in files {
+        let contents = match read_input(file_name) {
+            Some(c) => c,
+            None => {
+                code = 2;
+                continue;
+            },
+        };
+
+        let tokens = lexer::Lexer::new(contents.as_str()).run();
+        let (root, diagnostics) = parser::Parser::with_options(&tokens, parse_options.clone()).parse_recovering();
+
+        for d in diagnostics.iter() {
+            eprint!("{}", d.render(file_name, tokens.line_index()));
+        }
+        if diagnostics.iter().any(|d| d.is_error()) {
+            code = 2;
+            continue;
+        }
+
+        let formatted = format::format(&root, &tokens);
+        if file_name == "-" && !check {
+            print!("{}", formatted);
+            continue;
+        }
+        if formatted == contents {
+            continue;
+        }
+
+        if check {
+            println!("{}", file_name);
+            code = code.max(1);
+        } else if let Err(e) = fs::write(file_name, formatted) {
+            eprintln!("could not write {}: {}", file_name, e);
+            code = 2;
+        }
+    }
+
+    code
+}
+
+fn load_schema(file_name: &str, options: &ParseOptions) -> Option<Root> {
+    load_schema_with_tokens(file_name, options).map(|(root, _)| root)
+}
+
+// the tokens are kept to render diagnostics pointing into the schema
+fn load_schema_with_tokens(file_name: &str, options: &ParseOptions) -> Option<(Root, Tokens)> {
+    let contents = read_input(file_name)?;
+
+    let mut l = lexer::Lexer::new(contents.as_str());
+    let tokens = l.run();
+    let mut p = parser::Parser::with_options(&tokens, options.clone());
+
+    match p.parse() {
+        Ok(root) => Some((root, tokens)),
+        Err(e) => {
+            eprint!("{}", Diagnostic::from_parse_error(&e, tokens.line_index()).render(file_name, tokens.line_index()));
+            None
+        },
+    }
+}
+
+// `-` reads stdin, errors are reported here
+fn read_input(file_name: &str) -> Option<String> {
+    let read = if file_name == "-" {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents).map(|_| contents)
+    } else {
+        fs::read_to_string(file_name)
+    };
+
+    match read {
+        Ok(contents) => Some(contents),
+        Err(e) => {
+            eprintln!("could not read {}: {}", if file_name == "-" { "stdin" } else { file_name }, e);
+            None
+        },
+    }
+}
+
+// commands reading one schema take it from stdin when no file is given
+fn single_file<'a>(files: &'a [String], usage: &str) -> Option<&'a str> {
+    match files {
+        [] => Some("-"),
+        [file_name] => Some(file_name),
+        _ => {
+            eprintln!("{}", usage);
+            None
+        },
+    }
+}
+
+// the config of the project the current directory is in, `None` outside of one.
+// errors in the config are reported here
+#[cfg(feature = "config")]
+fn project() -> Result<Option<Config>, ()> {
+    let cwd = std::env::current_dir().map_err(|e| eprintln!("could not read the current directory: {}", e))?;
+    let path = match config::find(&cwd) {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    // file names in messages are shorter relative to where odetto-cli runs
+    let path = path.strip_prefix(&cwd).map(Path::to_path_buf).unwrap_or(path);
+
+    config::load(&path).map(Some).map_err(|e| eprintln!("{}", e))
+}
+
+// project configs need the `config` feature, without it every command works on its arguments only
+#[cfg(not(feature = "config"))]
+fn project() -> Result<Option<Config>, ()> {
+    Ok(None)
+}
+
+// the custom scalars of the project, `None` when its config is broken
+fn project_parse_options() -> Option<ParseOptions> {
+    project().ok().map(|config| config.map(|c| c.parse_options()).unwrap_or_default())
+}
+
+#[cfg(feature = "config")]
+fn read_project_schema(config: &Config) -> Option<Sources> {
+    config.read_schema().map_err(|e| eprintln!("{}", e)).ok()
+}
+
+#[cfg(not(feature = "config"))]
+fn read_project_schema(_: &Config) -> Option<Sources> {
+    None
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    _helpers::{GenerateError, GenerateResult},
+    ast::Root,
+    codegen::{GeneratedFile, sql, rust, typescript, graphql},
+};
+
+// `key=value` pairs given to a generator, e.g. `--option serde=true` on the command line
+pub type GeneratorOptions = BTreeMap<String, String>;
+
+// turns a resolved schema into files, registered under a `--target` name.
+// generators in other crates implement this and are linked into a custom binary with their own `Registry`
+pub trait Generator {
+    fn name(&self) -> &str;
+
+    fn generate(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>>;
+}
+
+// generators by name, in registration order
+pub struct Registry {
+    generators: Vec<Box<dyn Generator>>,
+}
+
+impl Registry {
+    // the generators that come with odetto
+    pub fn new() -> Registry {
+        let mut registry = Registry::empty();
+        registry.register(sql::Postgres);
+        registry.register(sql::Sqlite);
+        registry.register(sql::MySql);
+        registry.register(rust::Rust);
+        registry.register(typescript::TypeScript);
+        registry.register(graphql::GraphQl);
+        #[cfg(feature = "templates")]
+        registry.register(crate::codegen::template::Templates);
+        registry
+    }
+
+    pub fn empty() -> Registry {
+        Registry { generators: Vec::new() }
+    }
+
+    // replaces a generator registered under the same name
+    pub fn register(&mut self, generator: impl Generator + 'static) {
+        self.generators.retain(|g| g.name() != generator.name());
+        self.generators.push(Box::new(generator));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Generator> {
+        self.generators.iter().find(|g| g.name() == name).map(|g| g.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.generators.iter().map(|g| g.name()).collect()
+    }
+
+    pub fn generate(&self, target: &str, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+        match self.get(target) {
+            Some(generator) => generator.generate(root, options),
+            None => Err(GenerateError::UnknownTargetError(target.to_string())),
+        }
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        Registry::new()
+    }
+}
+
+// fails on options the generator doesn't know instead of silently ignoring typos
+pub fn check_options(target: &str, options: &GeneratorOptions, known: &[&str]) -> GenerateResult<()> {
+    match options.keys().find(|k| !known.contains(&k.as_str())) {
+        Some(option) => Err(GenerateError::UnknownOptionError(target.to_string(), option.clone())),
+        None => Ok(()),
+    }
+}
+
+pub fn bool_option(options: &GeneratorOptions, name: &str) -> GenerateResult<Option<bool>> {
+    match options.get(name).map(|v| v.as_str()) {
+        None => Ok(None),
+        Some("true") => Ok(Some(true)),
+        Some("false") => Ok(Some(false)),
+        Some(value) => Err(GenerateError::InvalidOptionError(name.to_string(), value.to_string(), String::from("true or false"))),
+    }
+}
+
+// a single file for generators producing one string
+pub fn single_file(path: &str, contents: String) -> Vec<GeneratedFile> {
+    vec![GeneratedFile { path: path.to_string(), contents }]
+}
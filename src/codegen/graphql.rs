@@ -0,0 +1,148 @@
+use crate::{
+    _helpers::GenerateResult,
+    ast::{Root, ModelTypeDef, EnumDef, FieldDef, FieldType, FieldTypeType},
+    codegen::{primary_key_field, camel_case, plural, doc_lines, GeneratedFile, Generator, GeneratorOptions, generator},
+};
+
+#[derive(Clone, Debug)]
+pub struct GraphQlOptions {
+    pub query: bool, // a `Query` type with a list and a get by id field per model
+    pub id_scalar: bool, // primary keys (`@primary`, `@id` or `id`) are typed as `ID`
+}
+
+impl Default for GraphQlOptions {
+    fn default() -> GraphQlOptions {
+        GraphQlOptions {
+            query: false,
+            id_scalar: true,
+        }
+    }
+}
+
+// `schema.graphql`, options are `query=true` and `id_scalar=false`
+pub struct GraphQl;
+
+impl Generator for GraphQl {
+    fn name(&self) -> &str {
+        "graphql"
+    }
+
+    fn generate(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+        generator::check_options(self.name(), options, &["query", "id_scalar"])?;
+
+        let mut graphql_options = GraphQlOptions::default();
+        if let Some(query) = generator::bool_option(options, "query")? {
+            graphql_options.query = query;
+        }
+        if let Some(id_scalar) = generator::bool_option(options, "id_scalar")? {
+            graphql_options.id_scalar = id_scalar;
+        }
+
+        Ok(generator::single_file("schema.graphql", generate(root, &graphql_options)))
+    }
+}
+
+// the schema as graphql sdl, doc strings become descriptions.
+// annotations and relations have no graphql equivalent and are left out
+pub fn generate(root: &Root, options: &GraphQlOptions) -> String {
+    let mut items: Vec<String> = Vec::new();
+    let scalars = custom_scalars(root);
+    if !scalars.is_empty() {
+        items.push(scalars.iter().map(|s| format!("scalar {}\n", s)).collect());
+    }
+    items.extend(root.types.iter().map(|m| object_type(root, m, options)));
+    items.extend(root.enums.iter().map(enum_type));
+
+    // a model called `Query` already is the query type
+    if options.query && root.model("Query").is_none() && !root.types.is_empty() {
+        items.push(query_type(root, options));
+    }
+
+    items.join("\n")
+}
+
+// scalars other than `Int` and `String` come from the project config and have to be declared
+fn custom_scalars(root: &Root) -> Vec<&str> {
+    let mut scalars: Vec<&str> = Vec::new();
+    for field in root.types.iter().flat_map(|m| m.fields.iter()) {
+        if let FieldType::Scalar(name, _) = &field.field_type {
+            if name != "Int" && name != "String" && !scalars.contains(&name.as_str()) {
+                scalars.push(name);
+            }
+        }
+    }
+    scalars
+}
+
+fn object_type(root: &Root, model: &ModelTypeDef, options: &GraphQlOptions) -> String {
+    let key = primary_key_field(root, model).map(|f| f.name.as_str());
+
+    let mut out = String::new();
+    write_description(&mut out, &model.doc, "");
+    out.push_str(&format!("type {} {{\n", model.name));
+    for field in model.fields.iter() {
+        let id = options.id_scalar && key == Some(field.name.as_str());
+
+        write_description(&mut out, &field.doc, "  ");
+        out.push_str(&format!("  {}: {}\n", field.name, field_type(field, id)));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn enum_type(enum_def: &EnumDef) -> String {
+    let mut out = String::new();
+    write_description(&mut out, &enum_def.doc, "");
+    out.push_str(&format!("enum {} {{\n", enum_def.name));
+    for variant in enum_def.variants.iter() {
+        write_description(&mut out, &variant.doc, "  ");
+        out.push_str(&format!("  {}\n", variant.name));
+    }
+    out.push_str("}\n");
+    out
+}
+
+// `movies: [Movie!]!` for every model and `movie(id: ID!): Movie` for the ones with a primary key
+fn query_type(root: &Root, options: &GraphQlOptions) -> String {
+    let mut out = String::from("type Query {\n");
+
+    for model in root.types.iter() {
+        out.push_str(&format!("  {}: [{}!]!\n", camel_case(&plural(&model.name)), model.name));
+
+        if let Some(key) = primary_key_field(root, model) {
+            let mut key_type = field_type(key, options.id_scalar);
+            if !key_type.ends_with('!') {
+                key_type.push('!');
+            }
+            out.push_str(&format!("  {}({}: {}): {}\n", camel_case(&model.name), key.name, key_type, model.name));
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn field_type(field: &FieldDef, id: bool) -> String {
+    let name = if id { "ID" } else { field.field_type.name() };
+    let required = if field.required { "!" } else { "" };
+
+    match field.type_type {
+        FieldTypeType::Basic => format!("{}{}", name, required),
+        FieldTypeType::Array => format!("[{}]{}", name, required),
+        FieldTypeType::RequiredArray => format!("[{}!]{}", name, required),
+    }
+}
+
+fn write_description(out: &mut String, doc: &Option<String>, indent: &str) {
+    if let Some(doc) = doc {
+        out.push_str(&format!("{}\"\"\"\n", indent));
+        for line in doc_lines(doc) {
+            if line.is_empty() {
+                out.push('\n');
+            } else {
+                out.push_str(&format!("{}{}\n", indent, line.replace("\"\"\"", "\\\"\"\"")));
+            }
+        }
+        out.push_str(&format!("{}\"\"\"\n", indent));
+    }
+}
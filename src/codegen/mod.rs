@@ -0,0 +1,139 @@
+pub mod sql;
+pub mod rust;
+pub mod typescript;
+pub mod graphql;
+pub mod generator;
+#[cfg(feature = "serde")]
+pub mod plugin;
+#[cfg(feature = "templates")]
+pub mod template;
+
+use crate::ast::{self, Root, ModelTypeDef, FieldDef, FieldType, FieldTypeType};
+
+pub use generator::{Generator, GeneratorOptions, Registry};
+
+// a file written by a generator, `path` is relative to the output directory
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedFile {
+    pub path: String,
+    pub contents: String,
+}
+
+// the scalar or enum field marked with `@primary`, or its alias `@id`, or else the one called `id`.
+// lists and relations can't be keys
+pub fn primary_key_field<'a>(root: &Root, model: &'a ModelTypeDef) -> Option<&'a FieldDef> {
+    let is_value = |f: &&FieldDef| f.type_type == FieldTypeType::Basic && match &f.field_type {
+        FieldType::Scalar(..) => true,
+        FieldType::Identfier(name, _) => root.enum_def(name).is_some(),
+    };
+    let is_marked = |f: &&FieldDef| ["primary", "id"].iter().any(|name| ast::annotation(&f.annotations, name).is_some());
+
+    model.fields.iter().filter(is_value).find(is_marked)
+        .or_else(|| model.fields.iter().filter(is_value).find(|f| f.name == "id"))
+}
+
+// `MovieReview`, `movieReview` and `HTTPServer` become `movie_review` and `http_server`
+pub fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+
+        out.extend(c.to_lowercase());
+    }
+
+    out
+}
+
+// `movie_review`, `DRAMA` and `HTTPServer` become `MovieReview`, `Drama` and `HttpServer`
+pub fn pascal_case(name: &str) -> String {
+    snake_case(name)
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+// `MovieReview` and `movie_review` become `movieReview`
+pub fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// naive english plural, good enough for type names: `Movie` -> `Movies`, `Category` -> `Categories`, `Box` -> `Boxes`
+pub fn plural(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let before_y = lower.chars().rev().nth(1);
+
+    if ["s", "x", "z", "ch", "sh"].iter().any(|end| lower.ends_with(end)) {
+        format!("{}es", name)
+    } else if lower.ends_with('y') && before_y.is_some_and(|c| !"aeiou".contains(c)) {
+        format!("{}ies", &name[..name.len() - 1])
+    } else {
+        format!("{}s", name)
+    }
+}
+
+// doc strings keep the indentation of the source, this strips it line by line
+pub fn doc_lines(doc: &str) -> Vec<&str> {
+    doc.lines().map(|l| l.trim()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snake_cases() {
+        assert_eq!(snake_case("Movie"), "movie");
+        assert_eq!(snake_case("MovieReview"), "movie_review");
+        assert_eq!(snake_case("movieReview"), "movie_review");
+        assert_eq!(snake_case("HTTPServer"), "http_server");
+        assert_eq!(snake_case("already_snake"), "already_snake");
+        assert_eq!(snake_case("Version2Name"), "version2_name");
+    }
+
+    #[test]
+    fn pascal_cases() {
+        assert_eq!(pascal_case("movie_review"), "MovieReview");
+        assert_eq!(pascal_case("DRAMA"), "Drama");
+        assert_eq!(pascal_case("HTTPServer"), "HttpServer");
+        assert_eq!(pascal_case("Movie"), "Movie");
+    }
+
+    #[test]
+    fn camel_cases() {
+        assert_eq!(camel_case("MovieReview"), "movieReview");
+        assert_eq!(camel_case("movie_review"), "movieReview");
+        assert_eq!(camel_case("User"), "user");
+    }
+
+    #[test]
+    fn plurals() {
+        assert_eq!(plural("Movie"), "Movies");
+        assert_eq!(plural("category"), "categories");
+        assert_eq!(plural("Day"), "Days");
+        assert_eq!(plural("Box"), "Boxes");
+        assert_eq!(plural("Address"), "Addresses");
+        assert_eq!(plural("Match"), "Matches");
+    }
+}
@@ -0,0 +1,136 @@
+use std::{env, io::Write, path::PathBuf, process::{Command, Stdio}, thread};
+
+use crate::{
+    _helpers::{GenerateError, GenerateResult},
+    ast::Root,
+    codegen::{GeneratedFile, Generator, GeneratorOptions},
+    diagnostics::{Diagnostic, Severity},
+    span::Span,
+};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// written as json to the plugin's stdin, `schema` has the shape documented in `ast.rs`
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PluginRequest {
+    pub version: u32,
+    pub target: String,
+    pub options: GeneratorOptions,
+    pub schema: Root,
+}
+
+// read as json from the plugin's stdout once it exits successfully
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct PluginResponse {
+    #[serde(default)]
+    pub files: Vec<GeneratedFile>,
+    #[serde(default)]
+    pub diagnostics: Vec<PluginDiagnostic>,
+}
+
+// `{ "severity": "error" | "warning", "message", "span", "code", "help" }`, only `severity` and `message` are required.
+// spans are byte offsets into the schema source, the same ones the plugin got in the request
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PluginDiagnostic {
+    pub severity: Severity,
+    pub message: String,
+    #[serde(default)]
+    pub span: Option<Span>,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub help: Option<String>,
+}
+
+impl PluginResponse {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    // the code defaults to the target so the user can tell which plugin complained
+    pub fn diagnostics(&self, target: &str) -> Vec<Diagnostic> {
+        self.diagnostics.iter().map(|d| {
+            let mut diagnostic = Diagnostic::error(d.code.as_deref().unwrap_or(target), d.message.clone());
+            diagnostic.severity = d.severity;
+            diagnostic.span = d.span;
+            diagnostic.help = d.help.clone();
+            diagnostic
+        }).collect()
+    }
+}
+
+// an executable speaking the plugin protocol, protoc style: the request goes to stdin and the
+// response comes back on stdout. stderr is left to the plugin for its own logging
+#[derive(Clone, Debug)]
+pub struct ExternalGenerator {
+    pub name: String,
+    pub program: PathBuf,
+}
+
+impl ExternalGenerator {
+    pub fn new(name: &str, program: impl Into<PathBuf>) -> ExternalGenerator {
+        ExternalGenerator { name: name.to_string(), program: program.into() }
+    }
+
+    // `odetto-gen-<name>` in one of the `PATH` directories
+    pub fn find(name: &str) -> Option<ExternalGenerator> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return None;
+        }
+
+        let file_name = format!("odetto-gen-{}{}", name, env::consts::EXE_SUFFIX);
+        env::split_paths(&env::var_os("PATH")?)
+            .map(|dir| dir.join(&file_name))
+            .find(|path| path.is_file())
+            .map(|program| ExternalGenerator::new(name, program))
+    }
+
+    pub fn run(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<PluginResponse> {
+        let fail = |message: String| GenerateError::GeneratorError(self.name.clone(), message);
+
+        let request = PluginRequest {
+            version: PROTOCOL_VERSION,
+            target: self.name.clone(),
+            options: options.clone(),
+            schema: root.clone(),
+        };
+        let request = serde_json::to_vec(&request).map_err(|e| fail(e.to_string()))?;
+
+        let mut child = Command::new(&self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| fail(format!("could not run {}: {}", self.program.display(), e)))?;
+
+        // written from another thread so a plugin answering before it read everything can't block us
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = thread::spawn(move || stdin.write_all(&request));
+
+        let output = child.wait_with_output().map_err(|e| fail(e.to_string()))?;
+        // a plugin may exit without reading the request, only its answer matters
+        let _ = writer.join();
+
+        if !output.status.success() {
+            return Err(fail(format!("{} exited with {}", self.program.display(), output.status)));
+        }
+
+        serde_json::from_slice(&output.stdout).map_err(|e| fail(format!("invalid response: {}", e)))
+    }
+}
+
+// registered like any other generator, warnings are dropped and the first error fails the generation
+impl Generator for ExternalGenerator {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn generate(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+        let response = self.run(root, options)?;
+
+        match response.diagnostics.iter().find(|d| d.severity == Severity::Error) {
+            Some(error) => Err(GenerateError::GeneratorError(self.name.clone(), error.message.clone())),
+            None => Ok(response.files),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{
+    _helpers::GenerateResult,
+    ast::{Root, ModelTypeDef, EnumDef, FieldDef, FieldType, FieldTypeType},
+    codegen::{snake_case, pascal_case, doc_lines, GeneratedFile, Generator, GeneratorOptions, generator},
+};
+
+#[derive(Clone, Debug)]
+pub struct RustOptions {
+    pub derives: Vec<String>, // e.g. `Clone`, `Debug`, `PartialEq`
+    pub serde: bool, // derive `serde::Serialize` and `serde::Deserialize` and keep the schema names on the wire
+}
+
+impl Default for RustOptions {
+    fn default() -> RustOptions {
+        RustOptions {
+            derives: vec![String::from("Clone"), String::from("Debug")],
+            serde: false,
+        }
+    }
+}
+
+// `schema.rs`, options are `derives=Clone,Debug` and `serde=true`
+pub struct Rust;
+
+impl Generator for Rust {
+    fn name(&self) -> &str {
+        "rust"
+    }
+
+    fn generate(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+        generator::check_options(self.name(), options, &["derives", "serde"])?;
+
+        let mut rust_options = RustOptions::default();
+        if let Some(derives) = options.get("derives") {
+            rust_options.derives = derives.split(',').map(|d| d.trim().to_string()).filter(|d| !d.is_empty()).collect();
+        }
+        if let Some(serde) = generator::bool_option(options, "serde")? {
+            rust_options.serde = serde;
+        }
+
+        Ok(generator::single_file("schema.rs", generate(root, &rust_options)))
+    }
+}
+
+// one struct per model and one enum per enum, in declaration order.
+// references that lead back to the model are boxed so the structs have a known size
+pub fn generate(root: &Root, options: &RustOptions) -> String {
+    let mut items = Vec::new();
+
+    for model in root.types.iter() {
+        items.push(model_struct(root, model, options));
+    }
+    for enum_def in root.enums.iter() {
+        items.push(enum_item(enum_def, options));
+    }
+
+    items.join("\n")
+}
+
+fn model_struct(root: &Root, model: &ModelTypeDef, options: &RustOptions) -> String {
+    let mut out = String::new();
+    write_doc(&mut out, &model.doc, "");
+    write_derives(&mut out, options);
+    out.push_str(&format!("pub struct {} {{\n", model.name));
+
+    for field in model.fields.iter() {
+        let name = identifier(&snake_case(&field.name));
+
+        write_doc(&mut out, &field.doc, "    ");
+        if options.serde && name.trim_start_matches("r#") != field.name {
+            out.push_str(&format!("    #[serde(rename = \"{}\")]\n", field.name));
+        }
+        out.push_str(&format!("    pub {}: {},\n", name, field_type(root, model, field)));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn enum_item(enum_def: &EnumDef, options: &RustOptions) -> String {
+    let mut out = String::new();
+    write_doc(&mut out, &enum_def.doc, "");
+    write_derives(&mut out, options);
+    out.push_str(&format!("pub enum {} {{\n", enum_def.name));
+
+    for variant in enum_def.variants.iter() {
+        let name = pascal_case(&variant.name);
+
+        write_doc(&mut out, &variant.doc, "    ");
+        if options.serde && name != variant.name {
+            out.push_str(&format!("    #[serde(rename = \"{}\")]\n", variant.name));
+        }
+        out.push_str(&format!("    {},\n", name));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+// `T!` is `T`, `T` is `Option<T>`, `[T!]!` is `Vec<T>` and `[T]` is `Option<Vec<Option<T>>>`
+fn field_type(root: &Root, model: &ModelTypeDef, field: &FieldDef) -> String {
+    let base = match &field.field_type {
+        FieldType::Scalar(name, _) if name == "Int" => String::from("i64"),
+        FieldType::Scalar(_, _) => String::from("String"),
+        FieldType::Identfier(name, _) => name.clone(),
+    };
+
+    let inner = match field.type_type {
+        FieldTypeType::Basic if is_recursive(root, &model.name, field) => format!("Box<{}>", base),
+        FieldTypeType::Basic => base,
+        FieldTypeType::Array => format!("Vec<Option<{}>>", base),
+        FieldTypeType::RequiredArray => format!("Vec<{}>", base),
+    };
+
+    if field.required { inner } else { format!("Option<{}>", inner) }
+}
+
+// a single reference that leads back to `model` through other single references
+fn is_recursive(root: &Root, model: &str, field: &FieldDef) -> bool {
+    let mut seen: Vec<&str> = Vec::new();
+    let mut stack = vec![field.field_type.name()];
+
+    while let Some(name) = stack.pop() {
+        if name == model {
+            return true;
+        }
+        if seen.contains(&name) {
+            continue;
+        }
+        seen.push(name);
+
+        if let Some(target) = root.model(name) {
+            for f in target.fields.iter().filter(|f| f.type_type == FieldTypeType::Basic) {
+                stack.push(f.field_type.name());
+            }
+        }
+    }
+
+    false
+}
+
+fn write_derives(out: &mut String, options: &RustOptions) {
+    let mut derives = options.derives.clone();
+    if options.serde {
+        derives.push(String::from("serde::Serialize"));
+        derives.push(String::from("serde::Deserialize"));
+    }
+
+    if !derives.is_empty() {
+        out.push_str(&format!("#[derive({})]\n", derives.join(", ")));
+    }
+}
+
+fn write_doc(out: &mut String, doc: &Option<String>, indent: &str) {
+    if let Some(doc) = doc {
+        for line in doc_lines(doc) {
+            if line.is_empty() {
+                out.push_str(&format!("{}///\n", indent));
+            } else {
+                out.push_str(&format!("{}/// {}\n", indent, line));
+            }
+        }
+    }
+}
+
+// field names that are rust keywords are written as raw identifiers,
+// the few that can't be raw get an underscore instead
+fn identifier(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false", "fn", "for",
+        "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct",
+        "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro",
+        "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+    ];
+
+    if ["crate", "self", "super"].contains(&name) {
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    fn generate_src(src: &str, options: &RustOptions) -> String {
+        let tokens = Lexer::new(src).run();
+        let root = Parser::new(&tokens).parse().unwrap();
+
+        generate(&root, options)
+    }
+
+    #[test]
+    fn names_and_renames() {
+        let options = RustOptions { derives: Vec::new(), serde: true };
+        let out = generate_src("type Movie { match: String releaseYear: Int! self: Int }", &options);
+
+        assert_eq!(out, "\
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Movie {
+    pub r#match: Option<String>,
+    #[serde(rename = \"releaseYear\")]
+    pub release_year: i64,
+    #[serde(rename = \"self\")]
+    pub self_: Option<i64>,
+}
+");
+    }
+
+    #[test]
+    fn recursive_references_are_boxed() {
+        let out = generate_src("type A { b: B! others: [A] } type B { a: A c: C } type C { id: Int }", &RustOptions::default());
+
+        assert!(out.contains("pub b: Box<B>,"));
+        assert!(out.contains("pub others: Option<Vec<Option<A>>>,"));
+        assert!(out.contains("pub a: Option<Box<A>>,"));
+        assert!(out.contains("pub c: Option<C>,"));
+    }
+}
@@ -0,0 +1,554 @@
+pub mod postgres;
+pub mod sqlite;
+pub mod mysql;
+
+use crate::{
+    ast::{self, Root, ModelTypeDef, FieldDef, FieldType, FieldTypeType, Annotation, AnnotationValue},
+    codegen::{self, snake_case, doc_lines},
+};
+
+pub use postgres::Postgres;
+pub use sqlite::Sqlite;
+pub use mysql::MySql;
+
+// dialect independent description of the tables a schema maps to.
+// everything is kept in declaration order so the generated sql is deterministic
+#[derive(Clone, Debug, Default)]
+pub struct SqlSchema {
+    pub enums: Vec<SqlEnum>,
+    pub tables: Vec<Table>, // model tables first, then join tables
+    pub foreign_keys: Vec<ForeignKey>,
+    pub indexes: Vec<Index>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SqlEnum {
+    pub name: String,
+    pub variants: Vec<String>,
+    pub doc: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Table {
+    pub name: String,
+    pub columns: Vec<Column>,
+    pub primary_key: Vec<String>,
+    pub doc: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Column {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub not_null: bool,
+    pub auto_increment: bool,
+    pub unique: bool,
+    pub indexed: bool, // part of a key or an index, some dialects need a bounded type for those
+    pub max_length: Option<usize>, // `@length(max: n)` on strings
+    pub default: Option<AnnotationValue>,
+    pub doc: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnType {
+    Integer,
+    Text,
+    Enum(String), // name of the `SqlEnum`
+    Array(Box<ColumnType>),
+}
+
+#[derive(Clone, Debug)]
+pub struct ForeignKey {
+    pub table: String,
+    pub column: String,
+    pub references: String,
+    pub referenced_column: String,
+    pub on_delete_cascade: bool,
+}
+
+impl ForeignKey {
+    pub fn name(&self) -> String {
+        format!("{}_{}_fkey", self.table, self.column)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Index {
+    pub name: String,
+    pub table: String,
+    pub columns: Vec<String>,
+}
+
+impl Column {
+    fn new(name: String, column_type: ColumnType) -> Column {
+        Column {
+            name,
+            column_type,
+            not_null: false,
+            auto_increment: false,
+            unique: false,
+            indexed: false,
+            max_length: None,
+            default: None,
+            doc: None,
+        }
+    }
+}
+
+// how docs end up in the generated sql
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommentStyle {
+    Statements, // `COMMENT ON ...` after the table
+    Inline, // `COMMENT '...'` on the column and the table
+    SqlComments, // `-- ...` lines, for databases without comment support
+}
+
+// the parts of the generated sql that differ between databases.
+// the generator walks the same `SqlSchema` for all of them
+pub trait SqlDialect {
+    fn quote(&self, identifier: &str) -> String;
+
+    fn column_type(&self, column: &Column, schema: &SqlSchema) -> String;
+
+    // the full definition of a single auto incremented primary key column, `name` is already quoted
+    fn auto_increment_column(&self, name: &str) -> String;
+
+    fn comment_style(&self) -> CommentStyle;
+
+    // statement run before the tables, e.g. `CREATE TYPE` for enums
+    fn create_enum(&self, _enum_def: &SqlEnum) -> Option<String> {
+        None
+    }
+
+    // foreign keys inside `CREATE TABLE` instead of `ALTER TABLE` once all tables exist
+    fn inline_foreign_keys(&self) -> bool {
+        false
+    }
+
+    // extra `CHECK (...)` condition for a column
+    fn check(&self, _column: &Column, _schema: &SqlSchema) -> Option<String> {
+        None
+    }
+
+    fn literal(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    fn bool_value(&self, value: bool) -> String {
+        String::from(if value { "TRUE" } else { "FALSE" })
+    }
+
+    // lists are stored as json by databases without native arrays
+    fn list_value(&self, values: &[AnnotationValue]) -> String {
+        self.literal(&json_list(values))
+    }
+}
+
+// maps models to tables:
+// - `@primary` (or `@id`) or a field called `id` is the primary key, models without one get an auto incremented `id`,
+//   or `_id` when a field already has that column
+// - single references become a `<field>_id` foreign key column
+// - scalar and enum arrays become array columns, the dialect decides how to store them
+// - arrays of models become join tables, unless the other side of the relation holds the foreign key
+// - `@db(name: "...")` renames tables, columns and enums, `@unique`, `@index` and `@default(value: ...)` are honoured
+// - `@index(fields: [...])` on a model indexes the columns of the listed fields together
+pub fn plan(root: &Root) -> SqlSchema {
+    let mut schema = SqlSchema::default();
+    let mut join_tables = Vec::new();
+    let mut join_keys = Vec::new();
+
+    for enum_def in root.enums.iter() {
+        schema.enums.push(SqlEnum {
+            name: db_name(&enum_def.annotations).unwrap_or_else(|| snake_case(&enum_def.name)),
+            variants: enum_def.variants.iter().map(|v| v.name.clone()).collect(),
+            doc: enum_def.doc.clone(),
+        });
+    }
+
+    for model in root.types.iter() {
+        let table = table_name(model);
+        let mut columns = Vec::new();
+        let mut field_columns = Vec::new();
+
+        if codegen::primary_key_field(root, model).is_none() {
+            let mut id = Column::new(surrogate_key(root, model), ColumnType::Integer);
+            id.not_null = true;
+            id.auto_increment = true;
+            columns.push(id);
+        }
+
+        for field in model.fields.iter() {
+            let target = match &field.field_type {
+                FieldType::Identfier(name, _) if root.enum_def(name).is_none() => root.model(name),
+                _ => None,
+            };
+
+            let target = if let Some(t) = target {
+                t
+            } else {
+                let column = value_column(root, model, field);
+                if annotation(field, "index").is_some() {
+                    schema.indexes.push(index(&table, vec![column.name.clone()]));
+                }
+                field_columns.push((field.name.as_str(), column.name.clone()));
+                columns.push(column);
+                continue;
+            };
+
+            let partner = relation_partner(root, model, field);
+            let owns = partner.is_none_or(|p| declared_before(root, (model, field), (target, p)));
+
+            if field.type_type == FieldTypeType::Basic {
+                let one_to_one = partner.is_some_and(|p| p.type_type == FieldTypeType::Basic);
+                // the required side of a one to one holds the key, ties go to the first declared
+                let holds_key = match partner {
+                    Some(p) if one_to_one && field.required != p.required => field.required,
+                    Some(_) if one_to_one => owns,
+                    _ => true,
+                };
+                if !holds_key {
+                    continue;
+                }
+
+                let (target_key, target_type) = primary_key(root, target);
+                let mut column = Column::new(db_name(&field.annotations).unwrap_or_else(|| format!("{}_id", snake_case(&field.name))), target_type);
+                column.not_null = field.required;
+                column.unique = one_to_one || annotation(field, "unique").is_some();
+                column.indexed = true;
+                column.doc = field.doc.clone();
+
+                schema.foreign_keys.push(ForeignKey {
+                    table: table.clone(),
+                    column: column.name.clone(),
+                    references: table_name(target),
+                    referenced_column: target_key,
+                    on_delete_cascade: false,
+                });
+                if annotation(field, "index").is_some() {
+                    schema.indexes.push(index(&table, vec![column.name.clone()]));
+                }
+                field_columns.push((field.name.as_str(), column.name.clone()));
+                columns.push(column);
+            } else if partner.is_none_or(|p| p.type_type != FieldTypeType::Basic) && owns {
+                join_tables.push(join_table(root, model, field, target, &mut join_keys));
+            }
+        }
+
+        // fields without a column of their own, like join table relations, are left out
+        for model_index in model.annotations.iter().filter(|a| a.name == "index") {
+            let names = match model_index.arg("fields") {
+                Some(AnnotationValue::List(names)) => names,
+                _ => continue,
+            };
+            let index_columns: Vec<String> = names.iter()
+                .filter_map(|name| match name {
+                    AnnotationValue::String(name) => field_columns.iter().find(|(f, _)| f == name).map(|(_, c)| c.clone()),
+                    _ => None,
+                })
+                .collect();
+            if index_columns.is_empty() {
+                continue;
+            }
+
+            for column in columns.iter_mut().filter(|c| index_columns.contains(&c.name)) {
+                column.indexed = true;
+            }
+            schema.indexes.push(index(&table, index_columns));
+        }
+
+        let primary_key = match codegen::primary_key_field(root, model) {
+            Some(f) => vec![column_name(f)],
+            None => vec![surrogate_key(root, model)],
+        };
+
+        schema.tables.push(Table { name: table, columns, primary_key, doc: model.doc.clone() });
+    }
+
+    schema.tables.extend(join_tables);
+    schema.foreign_keys.extend(join_keys);
+    schema
+}
+
+pub fn generate(root: &Root, dialect: &dyn SqlDialect) -> String {
+    render(&plan(root), dialect)
+}
+
+pub fn render(schema: &SqlSchema, dialect: &dyn SqlDialect) -> String {
+    let mut statements = Vec::new();
+
+    for enum_def in schema.enums.iter() {
+        if let Some(statement) = dialect.create_enum(enum_def) {
+            statements.push(statement);
+        }
+    }
+
+    for table in schema.tables.iter() {
+        statements.push(create_table(table, schema, dialect));
+    }
+
+    if !schema.foreign_keys.is_empty() && !dialect.inline_foreign_keys() {
+        let statement: String = schema.foreign_keys.iter().map(|fk| format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} {};\n",
+            dialect.quote(&fk.table),
+            dialect.quote(&fk.name()),
+            foreign_key(fk, dialect),
+        )).collect();
+        statements.push(statement);
+    }
+
+    if !schema.indexes.is_empty() {
+        let statement: String = schema.indexes.iter()
+            .map(|i| {
+                let columns: Vec<String> = i.columns.iter().map(|c| dialect.quote(c)).collect();
+                format!("CREATE INDEX {} ON {} ({});\n", dialect.quote(&i.name), dialect.quote(&i.table), columns.join(", "))
+            })
+            .collect();
+        statements.push(statement);
+    }
+
+    statements.join("\n")
+}
+
+fn create_table(table: &Table, schema: &SqlSchema, dialect: &dyn SqlDialect) -> String {
+    let style = dialect.comment_style();
+    let single_key = table.primary_key.len() == 1;
+
+    let mut lines: Vec<String> = table.columns.iter()
+        .map(|c| {
+            let line = column(c, single_key && table.primary_key[0] == c.name, schema, dialect);
+            match &c.doc {
+                Some(doc) if style == CommentStyle::SqlComments => format!("{}{}", sql_comment(doc, "    "), line),
+                _ => line,
+            }
+        })
+        .collect();
+    if !single_key {
+        let keys: Vec<String> = table.primary_key.iter().map(|k| dialect.quote(k)).collect();
+        lines.push(format!("PRIMARY KEY ({})", keys.join(", ")));
+    }
+    if dialect.inline_foreign_keys() {
+        for fk in schema.foreign_keys.iter().filter(|fk| fk.table == table.name) {
+            lines.push(foreign_key(fk, dialect));
+        }
+    }
+
+    let mut out = String::new();
+    if let (Some(doc), CommentStyle::SqlComments) = (&table.doc, style) {
+        out.push_str(&sql_comment(doc, ""));
+    }
+    out.push_str(&format!("CREATE TABLE {} (\n    {}\n)", dialect.quote(&table.name), lines.join(",\n    ")));
+    if let (Some(doc), CommentStyle::Inline) = (&table.doc, style) {
+        out.push_str(&format!(" COMMENT = {}", comment(doc, dialect)));
+    }
+    out.push_str(";\n");
+
+    if style == CommentStyle::Statements {
+        if let Some(doc) = &table.doc {
+            out.push_str(&format!("COMMENT ON TABLE {} IS {};\n", dialect.quote(&table.name), comment(doc, dialect)));
+        }
+        for c in table.columns.iter() {
+            if let Some(doc) = &c.doc {
+                out.push_str(&format!("COMMENT ON COLUMN {}.{} IS {};\n", dialect.quote(&table.name), dialect.quote(&c.name), comment(doc, dialect)));
+            }
+        }
+    }
+
+    out
+}
+
+fn column(column: &Column, primary_key: bool, schema: &SqlSchema, dialect: &dyn SqlDialect) -> String {
+    let name = dialect.quote(&column.name);
+    let mut out = if primary_key && column.auto_increment {
+        dialect.auto_increment_column(&name)
+    } else {
+        format!("{} {}", name, dialect.column_type(column, schema))
+    };
+
+    if primary_key && !column.auto_increment {
+        out.push_str(" PRIMARY KEY");
+    } else if column.not_null && !primary_key {
+        out.push_str(" NOT NULL");
+    }
+    if column.unique && !primary_key {
+        out.push_str(" UNIQUE");
+    }
+    if let Some(value) = &column.default {
+        out.push_str(&format!(" DEFAULT {}", default_value(value, dialect)));
+    }
+    if let Some(check) = dialect.check(column, schema) {
+        out.push_str(&format!(" CHECK ({})", check));
+    }
+    if let (Some(doc), CommentStyle::Inline) = (&column.doc, dialect.comment_style()) {
+        out.push_str(&format!(" COMMENT {}", comment(doc, dialect)));
+    }
+
+    out
+}
+
+fn default_value(value: &AnnotationValue, dialect: &dyn SqlDialect) -> String {
+    match value {
+        AnnotationValue::Int(i) => i.to_string(),
+        AnnotationValue::Float(n) => format!("{:?}", n),
+        AnnotationValue::String(s) | AnnotationValue::Identifier(s) => dialect.literal(s),
+        AnnotationValue::Bool(b) => dialect.bool_value(*b),
+        AnnotationValue::List(values) => dialect.list_value(values),
+    }
+}
+
+fn foreign_key(fk: &ForeignKey, dialect: &dyn SqlDialect) -> String {
+    format!(
+        "FOREIGN KEY ({}) REFERENCES {} ({}){}",
+        dialect.quote(&fk.column),
+        dialect.quote(&fk.references),
+        dialect.quote(&fk.referenced_column),
+        if fk.on_delete_cascade { " ON DELETE CASCADE" } else { "" },
+    )
+}
+
+fn comment(doc: &str, dialect: &dyn SqlDialect) -> String {
+    dialect.literal(&doc_lines(doc).join("\n"))
+}
+
+// `indent` is written after every line so the commented statement keeps its indentation
+fn sql_comment(doc: &str, indent: &str) -> String {
+    doc_lines(doc).iter().map(|l| format!("-- {}\n{}", l, indent)).collect()
+}
+
+fn json_list(values: &[AnnotationValue]) -> String {
+    let values: Vec<String> = values.iter().map(|v| match v {
+        AnnotationValue::String(s) | AnnotationValue::Identifier(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+        AnnotationValue::List(inner) => json_list(inner),
+        other => other.to_string(),
+    }).collect();
+
+    format!("[{}]", values.join(","))
+}
+
+// the column type of a scalar or an enum, foreign keys use the one of the key they reference
+fn base_type(root: &Root, name: &str) -> ColumnType {
+    match name {
+        "Int" => ColumnType::Integer,
+        name => match root.enum_def(name) {
+            Some(e) => ColumnType::Enum(db_name(&e.annotations).unwrap_or_else(|| snake_case(&e.name))),
+            // `String` and custom scalars from the project config
+            None => ColumnType::Text,
+        },
+    }
+}
+
+fn value_column(root: &Root, model: &ModelTypeDef, field: &FieldDef) -> Column {
+    let base = base_type(root, field.field_type.name());
+    let column_type = if field.type_type == FieldTypeType::Basic { base } else { ColumnType::Array(Box::new(base)) };
+    let is_key = codegen::primary_key_field(root, model).is_some_and(|k| k.name == field.name);
+
+    let mut column = Column::new(column_name(field), column_type);
+    column.not_null = field.required || is_key;
+    column.unique = annotation(field, "unique").is_some();
+    column.indexed = is_key || column.unique || annotation(field, "index").is_some();
+    column.max_length = match annotation(field, "length").and_then(|a| a.arg("max")) {
+        Some(AnnotationValue::Int(max)) if *max > 0 => Some(*max as usize),
+        _ => None,
+    };
+    column.default = annotation(field, "default").and_then(|a| a.arg("value")).cloned();
+    column.auto_increment = is_key && column.column_type == ColumnType::Integer && column.default.is_none();
+    column.doc = field.doc.clone();
+    column
+}
+
+// `<table>_<field>` holding both primary keys, rows go away with either side
+fn join_table(root: &Root, model: &ModelTypeDef, field: &FieldDef, target: &ModelTypeDef, foreign_keys: &mut Vec<ForeignKey>) -> Table {
+    let table = format!("{}_{}", table_name(model), snake_case(&field.name));
+    let (source_key, source_type) = primary_key(root, model);
+    let (target_key, target_type) = primary_key(root, target);
+
+    let source_column = format!("{}_id", snake_case(&model.name));
+    let target_column = if target.name == model.name {
+        format!("{}_id", snake_case(&field.name))
+    } else {
+        format!("{}_id", snake_case(&target.name))
+    };
+
+    let mut columns = Vec::new();
+    for (name, column_type, references, referenced_column) in [
+        (source_column, source_type, table_name(model), source_key),
+        (target_column, target_type, table_name(target), target_key),
+    ] {
+        let mut column = Column::new(name, column_type);
+        column.not_null = true;
+        column.indexed = true;
+
+        foreign_keys.push(ForeignKey { table: table.clone(), column: column.name.clone(), references, referenced_column, on_delete_cascade: true });
+        columns.push(column);
+    }
+
+    let primary_key = columns.iter().map(|c| c.name.clone()).collect();
+    Table { name: table, columns, primary_key, doc: None }
+}
+
+// the other side of a relation, declared with `->` on either of the two fields
+fn relation_partner<'a>(root: &'a Root, model: &ModelTypeDef, field: &FieldDef) -> Option<&'a FieldDef> {
+    if let Some(inverse) = root.relation_inverse(field) {
+        return Some(inverse);
+    }
+
+    root.model(field.field_type.name())?.fields.iter().find(|f| {
+        f.field_type.name() == model.name && f.relation.as_ref().is_some_and(|r| r.inverse == field.name)
+    })
+}
+
+fn declared_before(root: &Root, a: (&ModelTypeDef, &FieldDef), b: (&ModelTypeDef, &FieldDef)) -> bool {
+    let position = |(model, field): (&ModelTypeDef, &FieldDef)| {
+        let m = root.types.iter().position(|t| t.name == model.name).unwrap_or(0);
+        let f = model.fields.iter().position(|f| f.name == field.name).unwrap_or(0);
+        (m, f)
+    };
+
+    position(a) <= position(b)
+}
+
+// `<table>_<columns>_idx`
+fn index(table: &str, columns: Vec<String>) -> Index {
+    Index { name: format!("{}_{}_idx", table, columns.join("_")), table: table.to_string(), columns }
+}
+
+fn primary_key(root: &Root, model: &ModelTypeDef) -> (String, ColumnType) {
+    match codegen::primary_key_field(root, model) {
+        Some(f) => (column_name(f), base_type(root, f.field_type.name())),
+        None => (surrogate_key(root, model), ColumnType::Integer),
+    }
+}
+
+// the auto incremented key of a model without a key field, moved aside by fields that can't be
+// the key but whose column is called `id`, like a list
+fn surrogate_key(root: &Root, model: &ModelTypeDef) -> String {
+    let taken = |name: &str| model.fields.iter().any(|f| {
+        let is_model = matches!(&f.field_type, FieldType::Identfier(n, _) if root.model(n).is_some());
+        // relations have a `<field>_id` column unless it's renamed
+        (!is_model || db_name(&f.annotations).is_some()) && column_name(f) == name
+    });
+
+    let mut key = String::from("id");
+    while taken(&key) {
+        key.insert(0, '_');
+    }
+    key
+}
+
+pub fn table_name(model: &ModelTypeDef) -> String {
+    db_name(&model.annotations).unwrap_or_else(|| snake_case(&model.name))
+}
+
+fn column_name(field: &FieldDef) -> String {
+    db_name(&field.annotations).unwrap_or_else(|| snake_case(&field.name))
+}
+
+fn db_name(annotations: &[Annotation]) -> Option<String> {
+    match ast::annotation(annotations, "db")?.arg("name")? {
+        AnnotationValue::String(name) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+fn annotation<'a>(field: &'a FieldDef, name: &str) -> Option<&'a Annotation> {
+    ast::annotation(&field.annotations, name)
+}
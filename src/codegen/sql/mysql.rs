@@ -0,0 +1,67 @@
+use crate::{
+    ast::{Root, AnnotationValue},
+    _helpers::GenerateResult,
+    codegen::{GeneratedFile, Generator, GeneratorOptions, generator},
+};
+use super::{SqlDialect, SqlSchema, Column, ColumnType, CommentStyle};
+
+// enums are inline `ENUM(...)` columns, arrays are `JSON` and docs are `COMMENT` clauses.
+// `TEXT` can't be part of a key so indexed strings are `VARCHAR(255)` unless `@length` says otherwise
+pub struct MySql;
+
+pub fn generate(root: &Root) -> String {
+    super::generate(root, &MySql)
+}
+
+impl Generator for MySql {
+    fn name(&self) -> &str {
+        "mysql"
+    }
+
+    fn generate(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+        generator::check_options(self.name(), options, &[])?;
+        Ok(generator::single_file("schema.sql", generate(root)))
+    }
+}
+
+impl SqlDialect for MySql {
+    fn quote(&self, identifier: &str) -> String {
+        format!("`{}`", identifier.replace('`', "``"))
+    }
+
+    fn column_type(&self, column: &Column, schema: &SqlSchema) -> String {
+        match &column.column_type {
+            ColumnType::Integer => String::from("INT"),
+            ColumnType::Text => match column.max_length {
+                Some(max) => format!("VARCHAR({})", max),
+                None if column.indexed => String::from("VARCHAR(255)"),
+                None => String::from("TEXT"),
+            },
+            ColumnType::Enum(name) => {
+                let variants: Vec<String> = schema.enums.iter()
+                    .find(|e| e.name == *name)
+                    .map_or_else(Vec::new, |e| e.variants.iter().map(|v| self.literal(v)).collect());
+                format!("ENUM({})", variants.join(", "))
+            },
+            ColumnType::Array(_) => String::from("JSON"),
+        }
+    }
+
+    fn auto_increment_column(&self, name: &str) -> String {
+        format!("{} INT NOT NULL AUTO_INCREMENT PRIMARY KEY", name)
+    }
+
+    fn comment_style(&self) -> CommentStyle {
+        CommentStyle::Inline
+    }
+
+    // backslashes are escapes in mysql string literals
+    fn literal(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+    }
+
+    // json columns only take expressions as defaults
+    fn list_value(&self, values: &[AnnotationValue]) -> String {
+        format!("({})", self.literal(&super::json_list(values)))
+    }
+}
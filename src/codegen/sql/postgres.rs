@@ -0,0 +1,71 @@
+use crate::{
+    ast::{Root, AnnotationValue},
+    _helpers::GenerateResult,
+    codegen::{GeneratedFile, Generator, GeneratorOptions, generator},
+};
+use super::{SqlDialect, SqlSchema, SqlEnum, Column, ColumnType, CommentStyle};
+
+// enums become `CREATE TYPE`, arrays are native and docs are `COMMENT ON` statements.
+// foreign keys are added with `ALTER TABLE` once all tables exist so declaration order doesn't matter
+pub struct Postgres;
+
+pub fn generate(root: &Root) -> String {
+    super::generate(root, &Postgres)
+}
+
+impl Generator for Postgres {
+    fn name(&self) -> &str {
+        "postgres"
+    }
+
+    fn generate(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+        generator::check_options(self.name(), options, &[])?;
+        Ok(generator::single_file("schema.sql", generate(root)))
+    }
+}
+
+impl SqlDialect for Postgres {
+    fn quote(&self, identifier: &str) -> String {
+        format!("\"{}\"", identifier.replace('"', "\"\""))
+    }
+
+    fn column_type(&self, column: &Column, _schema: &SqlSchema) -> String {
+        type_name(&column.column_type, column.max_length, self)
+    }
+
+    fn auto_increment_column(&self, name: &str) -> String {
+        format!("{} SERIAL PRIMARY KEY", name)
+    }
+
+    fn comment_style(&self) -> CommentStyle {
+        CommentStyle::Statements
+    }
+
+    fn create_enum(&self, enum_def: &SqlEnum) -> Option<String> {
+        let variants: Vec<String> = enum_def.variants.iter().map(|v| self.literal(v)).collect();
+        let mut statement = format!("CREATE TYPE {} AS ENUM ({});\n", self.quote(&enum_def.name), variants.join(", "));
+        if let Some(doc) = &enum_def.doc {
+            statement.push_str(&format!("COMMENT ON TYPE {} IS {};\n", self.quote(&enum_def.name), super::comment(doc, self)));
+        }
+
+        Some(statement)
+    }
+
+    fn list_value(&self, values: &[AnnotationValue]) -> String {
+        if values.is_empty() {
+            return String::from("'{}'");
+        }
+
+        let values: Vec<String> = values.iter().map(|v| super::default_value(v, self)).collect();
+        format!("ARRAY[{}]", values.join(", "))
+    }
+}
+
+fn type_name(column_type: &ColumnType, max_length: Option<usize>, dialect: &Postgres) -> String {
+    match column_type {
+        ColumnType::Integer => String::from("INTEGER"),
+        ColumnType::Text => max_length.map_or_else(|| String::from("TEXT"), |max| format!("VARCHAR({})", max)),
+        ColumnType::Enum(name) => dialect.quote(name),
+        ColumnType::Array(inner) => format!("{}[]", type_name(inner, max_length, dialect)),
+    }
+}
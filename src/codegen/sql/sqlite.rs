@@ -0,0 +1,71 @@
+use crate::{
+    ast::Root,
+    _helpers::GenerateResult,
+    codegen::{GeneratedFile, Generator, GeneratorOptions, generator},
+};
+use super::{SqlDialect, SqlSchema, Column, ColumnType, CommentStyle};
+
+// sqlite has no enums, arrays or comments: enums are `TEXT` with a `CHECK`,
+// arrays are `TEXT` holding json and docs become `--` comments.
+// `ALTER TABLE` can't add constraints so foreign keys live inside `CREATE TABLE`
+pub struct Sqlite;
+
+pub fn generate(root: &Root) -> String {
+    super::generate(root, &Sqlite)
+}
+
+impl Generator for Sqlite {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn generate(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+        generator::check_options(self.name(), options, &[])?;
+        Ok(generator::single_file("schema.sql", generate(root)))
+    }
+}
+
+impl SqlDialect for Sqlite {
+    fn quote(&self, identifier: &str) -> String {
+        format!("\"{}\"", identifier.replace('"', "\"\""))
+    }
+
+    fn column_type(&self, column: &Column, _schema: &SqlSchema) -> String {
+        match column.column_type {
+            ColumnType::Integer => String::from("INTEGER"),
+            _ => String::from("TEXT"),
+        }
+    }
+
+    // only `INTEGER PRIMARY KEY` aliases the rowid
+    fn auto_increment_column(&self, name: &str) -> String {
+        format!("{} INTEGER PRIMARY KEY AUTOINCREMENT", name)
+    }
+
+    fn comment_style(&self) -> CommentStyle {
+        CommentStyle::SqlComments
+    }
+
+    fn inline_foreign_keys(&self) -> bool {
+        true
+    }
+
+    // sqlite doesn't enforce lengths, so `@length` is checked explicitly
+    fn check(&self, column: &Column, schema: &SqlSchema) -> Option<String> {
+        let name = self.quote(&column.name);
+
+        match &column.column_type {
+            ColumnType::Enum(enum_name) => {
+                let enum_def = schema.enums.iter().find(|e| e.name == *enum_name)?;
+                let variants: Vec<String> = enum_def.variants.iter().map(|v| self.literal(v)).collect();
+                Some(format!("{} IN ({})", name, variants.join(", ")))
+            },
+            ColumnType::Text => column.max_length.map(|max| format!("length({}) <= {}", name, max)),
+            _ => None,
+        }
+    }
+
+    fn bool_value(&self, value: bool) -> String {
+        String::from(if value { "1" } else { "0" })
+    }
+}
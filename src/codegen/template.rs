@@ -0,0 +1,135 @@
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use tera::{Context, Tera, Value};
+
+use crate::{
+    _helpers::{GenerateError, GenerateResult},
+    ast::Root,
+    codegen::{self, GeneratedFile, Generator, GeneratorOptions},
+};
+
+const TARGET: &str = "template";
+
+// a tera template, `path` is relative to the template directory and is rendered as a template too
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    pub path: String,
+    pub source: String,
+}
+
+// `--target template --option dir=<templates>`, every other option is handed to the templates as `options`
+pub struct Templates;
+
+impl Generator for Templates {
+    fn name(&self) -> &str {
+        TARGET
+    }
+
+    fn generate(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+        let dir = options.get("dir").ok_or_else(|| GenerateError::MissingOptionError(TARGET.to_string(), String::from("dir")))?;
+        render(root, &load(Path::new(dir))?, options)
+    }
+}
+
+// every file below `dir`, in path order
+pub fn load(dir: &Path) -> GenerateResult<Vec<Template>> {
+    let mut templates = Vec::new();
+    load_dir(dir, "", &mut templates)?;
+    templates.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(templates)
+}
+
+fn load_dir(dir: &Path, prefix: &str, templates: &mut Vec<Template>) -> GenerateResult<()> {
+    let fail = |e: std::io::Error| GenerateError::GeneratorError(TARGET.to_string(), format!("could not read {}: {}", dir.display(), e));
+
+    for entry in fs::read_dir(dir).map_err(fail)? {
+        let entry = entry.map_err(fail)?;
+        let path = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+
+        if entry.file_type().map_err(fail)?.is_dir() {
+            load_dir(&entry.path(), &format!("{}/", path), templates)?;
+        } else {
+            let source = fs::read_to_string(entry.path()).map_err(fail)?;
+            templates.push(Template { path, source });
+        }
+    }
+
+    Ok(())
+}
+
+// renders the templates against the schema in its serialized shape (see `ast.rs`):
+// - `schema`, `models`, `enums` and `options` are available everywhere
+// - a template whose path uses `model` is rendered once per model with `model` set, `enum` works the same way
+// - a `.tera` suffix is dropped from the output path
+// - paths starting with `_`, or in a directory that does, are only there to be included
+// - `snake_case`, `pascal_case`, `camel_case` and `plural` filters work on names
+pub fn render(root: &Root, templates: &[Template], options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+    let mut tera = Tera::default();
+    // the output is code, not html
+    tera.autoescape_on(Vec::new());
+    tera.register_filter("snake_case", name_filter(codegen::snake_case));
+    tera.register_filter("pascal_case", name_filter(codegen::pascal_case));
+    tera.register_filter("camel_case", name_filter(codegen::camel_case));
+    tera.register_filter("plural", name_filter(codegen::plural));
+    // added together so templates can `include` and `extend` each other by path
+    tera.add_raw_templates(templates.iter().map(|t| (t.path.as_str(), t.source.as_str()))).map_err(template_error)?;
+
+    let mut context = Context::new();
+    context.insert("schema", root);
+    context.insert("models", &root.types);
+    context.insert("enums", &root.enums);
+    context.insert("options", options);
+
+    let mut files = Vec::new();
+
+    for template in templates.iter().filter(|t| !t.path.split('/').any(|part| part.starts_with('_'))) {
+        let contexts: Vec<Context> = if uses(&template.path, "model") {
+            root.types.iter().map(|m| with(&context, "model", m)).collect()
+        } else if uses(&template.path, "enum") {
+            root.enums.iter().map(|e| with(&context, "enum", e)).collect()
+        } else {
+            vec![context.clone()]
+        };
+
+        for context in contexts.iter() {
+            let path = tera.render_str(&template.path, context).map_err(template_error)?;
+            let contents = tera.render(&template.path, context).map_err(template_error)?;
+
+            files.push(GeneratedFile { path: path.trim_end_matches(".tera").to_string(), contents });
+        }
+    }
+
+    Ok(files)
+}
+
+// whether a path template refers to `name`, e.g. `{{ model.name | snake_case }}.py`
+fn uses(path: &str, name: &str) -> bool {
+    let expression = regex::Regex::new(&format!(r"\{{\{{[^}}]*\b{}\b", name)).unwrap();
+    expression.is_match(path)
+}
+
+fn with<T: serde::Serialize>(context: &Context, name: &str, value: &T) -> Context {
+    let mut context = context.clone();
+    context.insert(name, value);
+    context
+}
+
+fn name_filter(f: fn(&str) -> String) -> impl tera::Filter {
+    move |value: &Value, _: &HashMap<String, Value>| match value.as_str() {
+        Some(name) => Ok(Value::String(f(name))),
+        None => Err(tera::Error::msg(format!("expected a name, got {}", value))),
+    }
+}
+
+// tera nests the useful part of the message, e.g. the missing variable, in the error's sources
+fn template_error(error: tera::Error) -> GenerateError {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(e) = source {
+        message.push_str(&format!(": {}", e));
+        source = e.source();
+    }
+
+    GenerateError::GeneratorError(TARGET.to_string(), message)
+}
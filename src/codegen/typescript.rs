@@ -0,0 +1,169 @@
+use crate::{
+    _helpers::{GenerateError, GenerateResult},
+    ast::{Root, ModelTypeDef, EnumDef, FieldDef, FieldType, FieldTypeType},
+    codegen::{GeneratedFile, doc_lines, Generator, GeneratorOptions, generator},
+};
+
+// how a field that isn't required is declared
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Nullability {
+    Optional, // `name?: T`
+    Null, // `name: T | null`
+}
+
+#[derive(Clone, Debug)]
+pub struct TypeScriptOptions {
+    pub nullability: Nullability,
+    pub file_per_model: bool, // `<Name>.d.ts` per model and enum instead of a single `index.d.ts`
+}
+
+impl Default for TypeScriptOptions {
+    fn default() -> TypeScriptOptions {
+        TypeScriptOptions {
+            nullability: Nullability::Optional,
+            file_per_model: false,
+        }
+    }
+}
+
+// options are `nullability=optional|null` and `file_per_model=true`
+pub struct TypeScript;
+
+impl Generator for TypeScript {
+    fn name(&self) -> &str {
+        "typescript"
+    }
+
+    fn generate(&self, root: &Root, options: &GeneratorOptions) -> GenerateResult<Vec<GeneratedFile>> {
+        generator::check_options(self.name(), options, &["nullability", "file_per_model"])?;
+
+        let mut ts_options = TypeScriptOptions::default();
+        match options.get("nullability").map(|n| n.as_str()) {
+            None => {},
+            Some("optional") => ts_options.nullability = Nullability::Optional,
+            Some("null") => ts_options.nullability = Nullability::Null,
+            Some(value) => return Err(GenerateError::InvalidOptionError(String::from("nullability"), value.to_string(), String::from("optional or null"))),
+        }
+        if let Some(file_per_model) = generator::bool_option(options, "file_per_model")? {
+            ts_options.file_per_model = file_per_model;
+        }
+
+        Ok(generate(root, &ts_options))
+    }
+}
+
+// an `interface` per model and a union of string literals per enum
+pub fn generate(root: &Root, options: &TypeScriptOptions) -> Vec<GeneratedFile> {
+    if !options.file_per_model {
+        let mut items: Vec<String> = root.types.iter().map(|m| interface(m, options)).collect();
+        items.extend(root.enums.iter().map(enum_type));
+
+        return vec![GeneratedFile { path: String::from("index.d.ts"), contents: items.join("\n") }];
+    }
+
+    let mut files = Vec::new();
+
+    for model in root.types.iter() {
+        let mut imports: Vec<&str> = model.fields.iter()
+            .filter_map(|f| match &f.field_type {
+                FieldType::Identfier(name, _) if *name != model.name => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        imports.sort_unstable();
+        imports.dedup();
+
+        let mut contents: String = imports.iter()
+            .map(|name| format!("import type {{ {} }} from \"./{}\";\n", name, name))
+            .collect();
+        if !imports.is_empty() {
+            contents.push('\n');
+        }
+        contents.push_str(&interface(model, options));
+
+        files.push(GeneratedFile { path: format!("{}.d.ts", model.name), contents });
+    }
+    for enum_def in root.enums.iter() {
+        files.push(GeneratedFile { path: format!("{}.d.ts", enum_def.name), contents: enum_type(enum_def) });
+    }
+
+    files
+}
+
+fn interface(model: &ModelTypeDef, options: &TypeScriptOptions) -> String {
+    let mut out = String::new();
+    write_doc(&mut out, &model.doc, "");
+    out.push_str(&format!("export interface {} {{\n", model.name));
+
+    for field in model.fields.iter() {
+        write_doc(&mut out, &field.doc, "    ");
+
+        let field_type = field_type(field);
+        if field.required {
+            out.push_str(&format!("    {}: {};\n", property(&field.name), field_type));
+        } else if options.nullability == Nullability::Optional {
+            out.push_str(&format!("    {}?: {};\n", property(&field.name), field_type));
+        } else {
+            out.push_str(&format!("    {}: {} | null;\n", property(&field.name), field_type));
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn enum_type(enum_def: &EnumDef) -> String {
+    let mut out = String::new();
+    write_doc(&mut out, &enum_def.doc, "");
+
+    let variants: Vec<String> = enum_def.variants.iter().map(|v| format!("\"{}\"", v.name)).collect();
+    out.push_str(&format!("export type {} = {};\n", enum_def.name, variants.join(" | ")));
+    out
+}
+
+// `[T!]` is `T[]` and `[T]` is `(T | null)[]`, the outer `!` is handled by the caller
+fn field_type(field: &FieldDef) -> String {
+    // custom scalars are sent as strings
+    let base = match &field.field_type {
+        FieldType::Scalar(name, _) if name == "Int" => "number",
+        FieldType::Scalar(_, _) => "string",
+        FieldType::Identfier(name, _) => name,
+    };
+
+    match field.type_type {
+        FieldTypeType::Basic => base.to_string(),
+        FieldTypeType::Array => format!("({} | null)[]", base),
+        FieldTypeType::RequiredArray => format!("{}[]", base),
+    }
+}
+
+// names that aren't valid javascript identifiers have to be quoted
+fn property(name: &str) -> String {
+    let valid = name.chars().enumerate().all(|(i, c)| c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+
+    if valid && !name.is_empty() {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+fn write_doc(out: &mut String, doc: &Option<String>, indent: &str) {
+    let doc = if let Some(d) = doc { d } else { return };
+    let lines = doc_lines(doc);
+
+    if lines.len() == 1 {
+        out.push_str(&format!("{}/** {} */\n", indent, lines[0].replace("*/", "*\\/")));
+        return;
+    }
+
+    out.push_str(&format!("{}/**\n", indent));
+    for line in lines {
+        if line.is_empty() {
+            out.push_str(&format!("{} *\n", indent));
+        } else {
+            out.push_str(&format!("{} * {}\n", indent, line.replace("*/", "*\\/")));
+        }
+    }
+    out.push_str(&format!("{} */\n", indent));
+}
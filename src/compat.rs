@@ -0,0 +1,231 @@
+use std::fmt;
+
+use crate::{
+    ast::{Root, FieldTypeType},
+    diff::{SchemaDiff, Change, FieldChange},
+};
+
+// ordered so the worst finding is the max
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Compatibility {
+    Safe,
+    Risky,
+    Breaking,
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compatibility::Safe => write!(f, "safe"),
+            Compatibility::Risky => write!(f, "risky"),
+            Compatibility::Breaking => write!(f, "breaking"),
+        }
+    }
+}
+
+// who is affected by the change
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Consumer {
+    Reader, // reads data, e.g. API clients consuming responses
+    Writer, // sends data, e.g. API clients building inputs
+    Database, // rows that are already stored
+    All, // the strictest of all of the above
+}
+
+impl Consumer {
+    pub fn from_name(name: &str) -> Option<Consumer> {
+        match name {
+            "reader" => Some(Consumer::Reader),
+            "writer" => Some(Consumer::Writer),
+            "database" => Some(Consumer::Database),
+            "all" => Some(Consumer::All),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub compatibility: Compatibility,
+    pub model: String,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.compatibility, self.message)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CompatReport {
+    pub findings: Vec<Finding>,
+}
+
+impl CompatReport {
+    pub fn worst(&self) -> Compatibility {
+        self.findings.iter().map(|f| f.compatibility).max().unwrap_or(Compatibility::Safe)
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.worst() == Compatibility::Breaking
+    }
+
+    pub fn count(&self, compatibility: Compatibility) -> usize {
+        self.findings.iter().filter(|f| f.compatibility == compatibility).count()
+    }
+}
+
+// per consumer verdict, reader first then writer then database
+struct Verdict(Compatibility, Compatibility, Compatibility);
+
+impl Verdict {
+    fn all(c: Compatibility) -> Verdict {
+        Verdict(c, c, c)
+    }
+
+    fn for_consumer(&self, consumer: Consumer) -> Compatibility {
+        match consumer {
+            Consumer::Reader => self.0,
+            Consumer::Writer => self.1,
+            Consumer::Database => self.2,
+            Consumer::All => self.0.max(self.1).max(self.2),
+        }
+    }
+}
+
+// `old` is the schema the diff starts from, it is used to find references to renamed models
+pub fn classify(old: &Root, diff: &SchemaDiff, consumer: Consumer) -> CompatReport {
+    use Compatibility::*;
+
+    let mut findings = Vec::new();
+    let mut push = |verdict: Verdict, model: &str, field: Option<&str>, message: String| {
+        findings.push(Finding {
+            compatibility: verdict.for_consumer(consumer),
+            model: model.to_string(),
+            field: field.map(|f| f.to_string()),
+            message,
+        });
+    };
+
+    for change in diff.changes.iter() {
+        match change {
+            Change::ModelAdded(m) => push(Verdict::all(Safe), &m.name, None, format!("model `{}` was added", m.name)),
+            Change::ModelRemoved(m) => push(Verdict::all(Breaking), &m.name, None, format!("model `{}` was removed", m.name)),
+            Change::ModelRenamed { from, to } => {
+                let referenced = old.types.iter()
+                    .flat_map(|m| m.fields.iter())
+                    .any(|f| f.field_type.name() == from);
+                let verdict = if referenced { Breaking } else { Risky };
+                push(Verdict::all(verdict), to, None, format!("model `{}` was renamed to `{}`{}", from, to, if referenced { " and is referenced by other models" } else { "" }));
+            },
+            Change::ModelDocChanged { model, .. } => push(Verdict::all(Safe), model, None, format!("doc string of `{}` changed", model)),
+            Change::ModelAnnotationsChanged { model, .. } => push(Verdict::all(Risky), model, None, format!("annotations of `{}` changed", model)),
+            Change::FieldAdded { model, field } => {
+                let name = format!("{}.{}", model, field.name);
+                let has_default = field.annotations.iter().any(|a| a.name == "default");
+
+                if field.required {
+                    let database = if has_default { Safe } else { Breaking };
+                    push(Verdict(Safe, Breaking, database), model, Some(&field.name), format!("required field `{}` was added", name));
+                } else {
+                    push(Verdict::all(Safe), model, Some(&field.name), format!("optional field `{}` was added", name));
+                }
+            },
+            Change::FieldRemoved { model, field } => {
+                push(Verdict(Breaking, Risky, Breaking), model, Some(&field.name), format!("field `{}.{}` was removed", model, field.name));
+            },
+            Change::FieldChanged { model, to, changes, .. } => {
+                let name = format!("{}.{}", model, to.name);
+
+                for field_change in changes.iter() {
+                    let (verdict, message) = match field_change {
+                        FieldChange::Type { from, to } => (Verdict::all(Breaking), format!("type of `{}` changed from `{}` to `{}`", name, from, to)),
+                        FieldChange::Required { from: false, to: true } => (Verdict(Safe, Breaking, Breaking), format!("field `{}` became required", name)),
+                        FieldChange::Required { .. } => (Verdict(Breaking, Safe, Safe), format!("field `{}` became optional", name)),
+                        FieldChange::Array { from, to } => match (from, to) {
+                            (FieldTypeType::Array, FieldTypeType::RequiredArray) => (Verdict(Safe, Breaking, Breaking), format!("items of `{}` became required", name)),
+                            (FieldTypeType::RequiredArray, FieldTypeType::Array) => (Verdict(Breaking, Safe, Safe), format!("items of `{}` became optional", name)),
+                            _ => (Verdict::all(Breaking), format!("`{}` changed from {} to {}", name, describe(from), describe(to))),
+                        },
+                        FieldChange::Annotations { .. } => (Verdict::all(Risky), format!("annotations of `{}` changed", name)),
+                        FieldChange::Doc { .. } => (Verdict::all(Safe), format!("doc string of `{}` changed", name)),
+                        FieldChange::Relation { .. } => (Verdict::all(Risky), format!("relation of `{}` changed", name)),
+                    };
+
+                    push(verdict, model, Some(&to.name), message);
+                }
+            },
+            Change::EnumAdded(e) => push(Verdict::all(Safe), &e.name, None, format!("enum `{}` was added", e.name)),
+            Change::EnumRemoved(e) => push(Verdict::all(Breaking), &e.name, None, format!("enum `{}` was removed", e.name)),
+            Change::EnumChanged { to, added_variants, removed_variants, .. } => {
+                for variant in removed_variants.iter() {
+                    push(Verdict(Risky, Breaking, Breaking), &to.name, None, format!("variant `{}.{}` was removed", to.name, variant));
+                }
+                for variant in added_variants.iter() {
+                    push(Verdict(Risky, Safe, Safe), &to.name, None, format!("variant `{}.{}` was added", to.name, variant));
+                }
+                if added_variants.is_empty() && removed_variants.is_empty() {
+                    push(Verdict::all(Safe), &to.name, None, format!("docs or annotations of enum `{}` changed", to.name));
+                }
+            },
+        }
+    }
+
+    CompatReport { findings }
+}
+
+fn describe(type_type: &FieldTypeType) -> &'static str {
+    match type_type {
+        FieldTypeType::Basic => "a single value",
+        FieldTypeType::Array => "a list",
+        FieldTypeType::RequiredArray => "a list of required items",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser, diff::diff};
+
+    fn report(old: &str, new: &str, consumer: Consumer) -> CompatReport {
+        let old_tokens = Lexer::new(old).run();
+        let new_tokens = Lexer::new(new).run();
+        let old = Parser::new(&old_tokens).parse().unwrap();
+        let new = Parser::new(&new_tokens).parse().unwrap();
+
+        classify(&old, &diff(&old, &new), consumer)
+    }
+
+    #[test]
+    fn required_depends_on_consumer() {
+        let old = "type Movie { name: String tags: [String] }";
+        let new = "type Movie { name: String! tags: [String!] }";
+
+        assert_eq!(report(old, new, Consumer::Reader).worst(), Compatibility::Safe);
+        assert_eq!(report(old, new, Consumer::Writer).worst(), Compatibility::Breaking);
+        assert_eq!(report(new, old, Consumer::Reader).worst(), Compatibility::Breaking);
+        assert_eq!(report(new, old, Consumer::Writer).worst(), Compatibility::Safe);
+    }
+
+    #[test]
+    fn added_fields() {
+        let old = "type Movie { name: String }";
+
+        assert!(!report(old, "type Movie { name: String rating: Int }", Consumer::All).is_breaking());
+        assert!(report(old, "type Movie { name: String rating: Int! }", Consumer::Database).is_breaking());
+        assert!(!report(old, "type Movie { name: String rating: Int! @default(value: 0) }", Consumer::Database).is_breaking());
+    }
+
+    #[test]
+    fn renames() {
+        let old = "type Film { id: Int } type Review { film: Film }";
+        let new = "type Movie { id: Int } type Review { film: Movie }";
+        let r = report(old, new, Consumer::All);
+
+        assert_eq!(r.findings.len(), 1);
+        assert!(r.is_breaking());
+        assert_eq!(report("type Film { id: Int }", "type Movie { id: Int }", Consumer::All).worst(), Compatibility::Risky);
+    }
+}
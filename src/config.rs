@@ -0,0 +1,235 @@
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+#[cfg(feature = "config")]
+use std::{fs, path::Path};
+
+use crate::{
+    codegen::GeneratorOptions,
+    diagnostics::Diagnostic,
+    lint::Rule,
+    parser::ParseOptions,
+    span::{Span, LineIndex},
+};
+#[cfg(feature = "config")]
+use crate::{
+    _helpers::{ConfigError, ConfigResult},
+    lexer,
+};
+
+// looked up in this order in the current directory and then its parents
+pub const FILE_NAMES: [&str; 3] = ["odetto.yml", "odetto.yaml", "odetto.toml"];
+
+// a project file, e.g. as yaml:
+//
+//   schema: [schema/*.odet]
+//   scalars: [DateTime]
+//   lint: [missing-doc, missing-primary-key]
+//   targets:
+//     - target: postgres
+//       out: gen/sql
+//     - target: typescript
+//       out: gen/ts
+//       options: { file_per_model: true }
+//
+// every key is optional, `schema` defaults to the `.odet` files next to the config
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(default, deny_unknown_fields))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub schema: Vec<String>, // globs relative to `dir`, the files are parsed as one schema
+    pub scalars: Vec<String>,
+    pub lint: Vec<String>,
+    pub targets: Vec<TargetConfig>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    pub dir: PathBuf, // the directory holding the config file
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            schema: vec![String::from("*.odet")],
+            scalars: Vec::new(),
+            lint: Vec::new(),
+            targets: Vec::new(),
+            dir: PathBuf::new(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(deny_unknown_fields))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetConfig {
+    pub target: String,
+    pub out: String, // relative to the config file
+    #[cfg_attr(feature = "config", serde(default))]
+    pub options: BTreeMap<String, OptionValue>,
+}
+
+// generator options are strings, the config also takes the other scalars so `true` doesn't need quotes
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(untagged))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionValue::Bool(b) => write!(f, "{}", b),
+            OptionValue::Int(i) => write!(f, "{}", i),
+            OptionValue::Float(x) => write!(f, "{}", x),
+            OptionValue::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl TargetConfig {
+    pub fn options(&self) -> GeneratorOptions {
+        self.options.iter().map(|(k, v)| (k.clone(), v.to_string())).collect()
+    }
+}
+
+impl Config {
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions { scalars: self.scalars.clone() }
+    }
+
+    // names are checked when the config is loaded
+    pub fn lint_rules(&self) -> Vec<Rule> {
+        self.lint.iter().filter_map(|name| Rule::from_name(name)).collect()
+    }
+
+    pub fn out_dir(&self, target: &TargetConfig) -> PathBuf {
+        self.dir.join(&target.out)
+    }
+
+    // the schema files in pattern order, each pattern sorted by path.
+    // a file matched by several patterns is only read once
+    #[cfg(feature = "config")]
+    pub fn schema_files(&self) -> ConfigResult<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = Vec::new();
+
+        for pattern in self.schema.iter() {
+            let full = self.dir.join(pattern);
+            let paths = glob::glob(&full.to_string_lossy()).map_err(|e| ConfigError::PatternError(pattern.clone(), e.to_string()))?;
+
+            let mut matched = false;
+            for path in paths {
+                let path = path.map_err(|e| ConfigError::ReadError(e.path().display().to_string(), e.error().to_string()))?;
+                matched = true;
+                if path.is_file() && !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+            if !matched {
+                return Err(ConfigError::NoSchemaError(pattern.clone()));
+            }
+        }
+
+        Ok(files)
+    }
+
+    #[cfg(feature = "config")]
+    pub fn read_schema(&self) -> ConfigResult<Sources> {
+        let mut sources = Sources::default();
+
+        for path in self.schema_files()? {
+            let contents = fs::read_to_string(&path).map_err(|e| ConfigError::ReadError(path.display().to_string(), e.to_string()))?;
+            sources.push(&path.display().to_string(), &contents);
+        }
+
+        Ok(sources)
+    }
+}
+
+// the nearest config file in `dir` or one of its parents
+#[cfg(feature = "config")]
+pub fn find(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .flat_map(|d| FILE_NAMES.iter().map(move |name| d.join(name)))
+        .find(|path| path.is_file())
+}
+
+#[cfg(feature = "config")]
+pub fn load(path: &Path) -> ConfigResult<Config> {
+    let file_name = path.display().to_string();
+    let src = fs::read_to_string(path).map_err(|e| ConfigError::ReadError(file_name.clone(), e.to_string()))?;
+
+    let mut config = parse(&file_name, &src)?;
+    config.dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    Ok(config)
+}
+
+// toml when the file name says so, yaml otherwise
+#[cfg(feature = "config")]
+pub fn parse(file_name: &str, src: &str) -> ConfigResult<Config> {
+    let config: Config = if file_name.ends_with(".toml") {
+        toml::from_str(src).map_err(|e| ConfigError::SyntaxError(file_name.to_string(), e.message().to_string()))?
+    } else if src.trim().is_empty() {
+        // an empty yaml document is null rather than an empty mapping
+        Config::default()
+    } else {
+        serde_yaml::from_str(src).map_err(|e| ConfigError::SyntaxError(file_name.to_string(), e.to_string()))?
+    };
+
+    if let Some(rule) = config.lint.iter().find(|name| Rule::from_name(name).is_none()) {
+        return Err(ConfigError::UnknownLintError(rule.clone()));
+    }
+    if let Some(scalar) = config.scalars.iter().find(|name| !lexer::is_identifier(name)) {
+        return Err(ConfigError::InvalidScalarError(scalar.clone()));
+    }
+
+    Ok(config)
+}
+
+// several schema files parsed as one, spans in the parsed schema point into `text`
+#[derive(Clone, Debug, Default)]
+pub struct Sources {
+    pub text: String,
+    pub files: Vec<SourceFile>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceFile {
+    pub name: String,
+    pub span: Span, // where the file's contents are in `text`
+}
+
+impl Sources {
+    // files are separated by a newline so a declaration can't run into the next file
+    pub fn push(&mut self, name: &str, contents: &str) {
+        let start = self.text.len();
+        self.text.push_str(contents);
+        self.files.push(SourceFile { name: name.to_string(), span: Span::new(start, self.text.len()) });
+
+        if !contents.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+
+    // the file holding `offset`, the end of the text belongs to the last file
+    pub fn file(&self, offset: usize) -> Option<&SourceFile> {
+        self.files.iter().rev().find(|f| f.span.start <= offset)
+    }
+
+    // renders the diagnostic against the file its span is in
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let file = match diagnostic.span.and_then(|s| self.file(s.start)).or_else(|| self.files.first()) {
+            Some(f) => f,
+            None => return diagnostic.render("", &LineIndex::new("")),
+        };
+
+        let mut diagnostic = diagnostic.clone();
+        diagnostic.span = diagnostic.span.map(|s| {
+            let shift = |offset: usize| offset.clamp(file.span.start, file.span.end) - file.span.start;
+            Span::new(shift(s.start), shift(s.end))
+        });
+
+        diagnostic.render(&file.name, &LineIndex::new(&self.text[file.span.start..file.span.end]))
+    }
+}
@@ -0,0 +1,363 @@
+use std::fmt;
+
+use crate::{
+    lexer::{Lexer, Token, TokenType},
+    span::Span,
+};
+
+// the concrete syntax tree keeps every token with the whitespace and comments around it,
+// printing it gives back the source byte for byte. it is built for any input, tokens that
+// don't fit the grammar are kept as children of the node they were found in
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeKind {
+    Root,
+    Model, // doc string, `type` keyword, name, annotations and the body
+    Enum,
+    Field,
+    Variant,
+    FieldType, // `Type` or `[Type!]`, without the outer `!`
+    Relation, // `-> inverse`
+    Annotation, // `@name(arg: value, ...)`
+}
+
+#[derive(Clone, Debug)]
+pub struct SyntaxNode {
+    pub kind: NodeKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+// `leading` trivia is everything since the previous token's line break,
+// `trailing` trivia runs up to and including the next line break
+#[derive(Clone, Debug)]
+pub struct SyntaxToken {
+    pub t: TokenType,
+    pub text: String, // as written, unlike `Token::value`
+    pub span: Span,
+    pub leading: Vec<Token>,
+    pub trailing: Vec<Token>,
+}
+
+pub fn parse(src: &str) -> SyntaxNode {
+    let tokens = Lexer::lossless(src).run();
+    let mut trivia = tokens.trivia().iter().peekable();
+    let mut syntax_tokens: Vec<SyntaxToken> = Vec::new();
+
+    for token in tokens.into_iter() {
+        let mut leading = Vec::new();
+        while let Some(t) = trivia.next_if(|t| t.span.start < token.span.start) {
+            leading.push(t.clone());
+        }
+
+        syntax_tokens.push(SyntaxToken {
+            t: token.t.clone(),
+            text: src[token.span.start..token.span.end].to_string(),
+            span: token.span,
+            leading,
+            trailing: Vec::new(),
+        });
+    }
+
+    // the trivia after the previous token that ends its line moves over to it
+    for i in 1..syntax_tokens.len() {
+        let line_end = syntax_tokens[i].leading.iter()
+            .position(|t| t.value.contains('\n'))
+            .map_or(syntax_tokens[i].leading.len(), |p| p + 1);
+        let trailing: Vec<Token> = syntax_tokens[i].leading.drain(..line_end).collect();
+        syntax_tokens[i - 1].trailing = trailing;
+    }
+
+    Builder { tokens: syntax_tokens.into_iter().peekable() }.root()
+}
+
+impl SyntaxNode {
+    // from the first to the last token, without the trivia around them
+    pub fn span(&self) -> Span {
+        let tokens = self.tokens();
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => Span::default(),
+        }
+    }
+
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(n) => tokens.extend(n.tokens()),
+                SyntaxElement::Token(t) => tokens.push(t),
+            }
+        }
+        tokens
+    }
+
+    // every node of `kind` below this one, in source order
+    pub fn descendants(&self, kind: NodeKind) -> Vec<&SyntaxNode> {
+        let mut nodes = Vec::new();
+        for child in self.children.iter() {
+            if let SyntaxElement::Node(n) = child {
+                if n.kind == kind {
+                    nodes.push(n);
+                }
+                nodes.extend(n.descendants(kind));
+            }
+        }
+        nodes
+    }
+
+    // the first direct child token of type `t`, e.g. the name of a model
+    pub fn token(&self, t: TokenType) -> Option<&SyntaxToken> {
+        self.children.iter().find_map(|c| match c {
+            SyntaxElement::Token(token) if token.t == t => Some(token),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(n) => write!(f, "{}", n)?,
+                SyntaxElement::Token(t) => write!(f, "{}", t)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for t in self.leading.iter() {
+            write!(f, "{}", t.value)?;
+        }
+        write!(f, "{}", self.text)?;
+        for t in self.trailing.iter() {
+            write!(f, "{}", t.value)?;
+        }
+        Ok(())
+    }
+}
+
+struct Builder {
+    tokens: std::iter::Peekable<std::vec::IntoIter<SyntaxToken>>,
+}
+
+impl Builder {
+    fn root(mut self) -> SyntaxNode {
+        let mut root = SyntaxNode { kind: NodeKind::Root, children: Vec::new() };
+
+        while let Some(t) = self.peek() {
+            let definition = match t {
+                TokenType::DocString | TokenType::OpPlus | TokenType::OpMinus => self.definition(),
+                TokenType::FieldType | TokenType::Enum => self.definition(),
+                _ => None,
+            };
+
+            match definition {
+                Some(node) => root.children.push(SyntaxElement::Node(node)),
+                None => self.bump(&mut root),
+            }
+        }
+
+        root
+    }
+
+    fn peek(&mut self) -> Option<TokenType> {
+        self.tokens.peek().map(|t| t.t.clone())
+    }
+
+    fn bump(&mut self, node: &mut SyntaxNode) {
+        if let Some(t) = self.tokens.next() {
+            node.children.push(SyntaxElement::Token(t));
+        }
+    }
+
+    fn eat(&mut self, node: &mut SyntaxNode, t: TokenType) -> bool {
+        if self.peek() == Some(t) {
+            self.bump(node);
+            true
+        } else {
+            false
+        }
+    }
+
+    // `"""doc""" type Name @annotation { ... }`, migrations can put `+` or `-` in front of the keyword
+    fn definition(&mut self) -> Option<SyntaxNode> {
+        let mut node = SyntaxNode { kind: NodeKind::Model, children: Vec::new() };
+
+        self.eat(&mut node, TokenType::DocString);
+        if !self.eat(&mut node, TokenType::OpPlus) {
+            self.eat(&mut node, TokenType::OpMinus);
+        }
+
+        match self.peek() {
+            Some(TokenType::FieldType) => {},
+            Some(TokenType::Enum) => node.kind = NodeKind::Enum,
+            _ if node.children.is_empty() => return None,
+            _ => return Some(node),
+        }
+
+        self.bump(&mut node);
+        self.eat(&mut node, TokenType::Identifier);
+        self.annotations(&mut node);
+
+        if !self.eat(&mut node, TokenType::CurlyL) {
+            return Some(node);
+        }
+
+        while let Some(t) = self.peek() {
+            if t == TokenType::CurlyR || t == TokenType::FieldType || t == TokenType::Enum {
+                break;
+            }
+
+            let member = if node.kind == NodeKind::Model { self.field() } else { self.variant() };
+            match member {
+                Some(member) => node.children.push(SyntaxElement::Node(member)),
+                None => self.bump(&mut node),
+            }
+        }
+
+        self.eat(&mut node, TokenType::CurlyR);
+
+        Some(node)
+    }
+
+    // `"""doc""" name: [Type!]! -> inverse @annotation`, with an optional `+` or `-` marker
+    fn field(&mut self) -> Option<SyntaxNode> {
+        let mut node = SyntaxNode { kind: NodeKind::Field, children: Vec::new() };
+
+        self.eat(&mut node, TokenType::DocString);
+        if !self.eat(&mut node, TokenType::OpPlus) {
+            self.eat(&mut node, TokenType::OpMinus);
+        }
+        if !self.eat(&mut node, TokenType::Identifier) {
+            return if node.children.is_empty() { None } else { Some(node) };
+        }
+        if !self.eat(&mut node, TokenType::Colon) {
+            return Some(node);
+        }
+
+        let mut field_type = SyntaxNode { kind: NodeKind::FieldType, children: Vec::new() };
+        let array = self.eat(&mut field_type, TokenType::BracketL);
+        if self.peek().is_some_and(|t| is_type_name(&t)) {
+            self.bump(&mut field_type);
+        }
+        if array {
+            self.eat(&mut field_type, TokenType::OpExclamation);
+            self.eat(&mut field_type, TokenType::BracketR);
+        }
+        if !field_type.children.is_empty() {
+            node.children.push(SyntaxElement::Node(field_type));
+        }
+
+        self.eat(&mut node, TokenType::OpExclamation);
+
+        if self.peek() == Some(TokenType::OpArrow) {
+            let mut relation = SyntaxNode { kind: NodeKind::Relation, children: Vec::new() };
+            self.bump(&mut relation);
+            self.eat(&mut relation, TokenType::Identifier);
+            node.children.push(SyntaxElement::Node(relation));
+        }
+
+        self.annotations(&mut node);
+
+        Some(node)
+    }
+
+    fn variant(&mut self) -> Option<SyntaxNode> {
+        let mut node = SyntaxNode { kind: NodeKind::Variant, children: Vec::new() };
+
+        self.eat(&mut node, TokenType::DocString);
+        self.eat(&mut node, TokenType::Identifier);
+
+        if node.children.is_empty() { None } else { Some(node) }
+    }
+
+    // the arguments are kept as flat tokens up to the closing parenthesis
+    fn annotations(&mut self, parent: &mut SyntaxNode) {
+        while self.peek() == Some(TokenType::At) {
+            let mut node = SyntaxNode { kind: NodeKind::Annotation, children: Vec::new() };
+            self.bump(&mut node);
+
+            if self.peek().is_some_and(|t| is_type_name(&t) || t == TokenType::FieldType || t == TokenType::Enum) {
+                self.bump(&mut node);
+            }
+
+            if self.eat(&mut node, TokenType::ParenL) {
+                while let Some(t) = self.peek() {
+                    if t == TokenType::CurlyL || t == TokenType::CurlyR || t == TokenType::EOF {
+                        break;
+                    }
+                    self.bump(&mut node);
+                    if t == TokenType::ParenR {
+                        break;
+                    }
+                }
+            }
+
+            parent.children.push(SyntaxElement::Node(node));
+        }
+    }
+}
+
+fn is_type_name(t: &TokenType) -> bool {
+    *t == TokenType::Identifier || *t == TokenType::TInt || *t == TokenType::TString
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prints_back_the_source() {
+        let sources = [
+            "",
+            "  \n# only a comment",
+            "# header\r\n\r\ntype Movie @db(name: \"movies\") { # movies\n\t\"\"\"\n\tthe title\n\t\"\"\"\n  title: [String!]! -> x @a(b: [1, 2.5]) #end \n}\n",
+            "type { name: : -> @ ( } enum E { A \"\"\"\"\"\" B } \"\"\" never closed",
+            "+type A { -b +c: Int! } -enum E ~ é",
+        ];
+
+        for src in sources.iter() {
+            assert_eq!(parse(src).to_string(), *src);
+        }
+    }
+
+    #[test]
+    fn trivia_is_attached_to_tokens() {
+        let root = parse("# movies\ntype Movie {  # the body\n    name: String # the name\n}\n");
+        let tokens = root.tokens();
+
+        assert_eq!(tokens[0].text, "type");
+        assert_eq!(tokens[0].leading.iter().map(|t| t.value.as_str()).collect::<Vec<_>>(), vec!["# movies", "\n"]);
+        assert_eq!(tokens[2].trailing.iter().map(|t| t.value.as_str()).collect::<Vec<_>>(), vec!["  ", "# the body", "\n"]);
+        assert_eq!(tokens[3].leading[0].value, "    ");
+        assert_eq!(tokens[5].trailing[1].t, TokenType::Comment);
+        assert_eq!(tokens.last().unwrap().t, TokenType::EOF);
+    }
+
+    #[test]
+    fn nodes() {
+        let root = parse("type Movie @db(name: \"movies\") { cast: [Actor!]! -> movies @index } enum Genre { \"\"\"funny\"\"\" COMEDY DRAMA }");
+
+        let model = &root.descendants(NodeKind::Model)[0];
+        assert_eq!(model.token(TokenType::Identifier).unwrap().text, "Movie");
+        assert_eq!(model.descendants(NodeKind::Annotation).len(), 2);
+
+        let field = &root.descendants(NodeKind::Field)[0];
+        assert_eq!(field.to_string(), "cast: [Actor!]! -> movies @index ");
+        assert_eq!(field.descendants(NodeKind::FieldType)[0].to_string(), "[Actor!]");
+        assert_eq!(field.descendants(NodeKind::Relation)[0].to_string(), "-> movies ");
+
+        let variants = root.descendants(NodeKind::Variant);
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0].span(), Span::new(81, 99));
+    }
+}
@@ -0,0 +1,255 @@
+use std::fmt;
+
+use crate::{
+    _helpers::ParseError,
+    span::{Span, LineIndex},
+};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: String,
+    pub message: String,
+    pub span: Option<Span>,
+    pub label: Option<String>, // printed next to the underline
+    pub help: Option<String>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &str, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code: code.to_string(),
+            message: message.into(),
+            span: None,
+            label: None,
+            help: None,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn warning(code: &str, message: impl Into<String>) -> Diagnostic {
+        Diagnostic { severity: Severity::Warning, ..Diagnostic::error(code, message) }
+    }
+
+    pub fn with_span(mut self, span: Span) -> Diagnostic {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Diagnostic {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Diagnostic {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub fn from_parse_error(error: &ParseError, index: &LineIndex) -> Diagnostic {
+        let code = error.code();
+        let text = error.span()
+            .and_then(|s| index.source().get(s.start..s.end))
+            .unwrap_or("")
+            .to_string();
+
+        let diagnostic = match error {
+            ParseError::UnknownError => Diagnostic::error(code, "unknown parsing error"),
+            ParseError::NoFieldsError => Diagnostic::error(code, "no fields present on type")
+                .with_help("declare at least one field, e.g. `id: Int`"),
+            ParseError::DuplicateFieldIdentifierError(_) => Diagnostic::error(code, format!("duplicate field identifier `{}`", text))
+                .with_label("field already declared on this type")
+                .with_help("rename or remove one of the fields"),
+            ParseError::DuplicateModelIdentifierError(_) => Diagnostic::error(code, format!("duplicate model identifier `{}`", text))
+                .with_label("type already declared")
+                .with_help("rename or remove one of the types"),
+            ParseError::MissingColonError(_) => Diagnostic::error(code, "missing colon to indicate type on field")
+                .with_label("expected `:`")
+                .with_help("fields are declared as `name: Type`"),
+            ParseError::MissingFieldTypeError(_) => Diagnostic::error(code, "missing type on field")
+                .with_label("expected a type after this"),
+            ParseError::GenericFieldTypeError(_) => Diagnostic::error(code, "something is wrong with the type on field")
+                .with_label("invalid field type"),
+            ParseError::GenericFieldParsingError(_, message) => Diagnostic::error(code, "could not parse field type")
+                .with_label("invalid field type")
+                .with_note(message.clone()),
+            ParseError::MissingRightBracketError(_) => Diagnostic::error(code, "missing `}` to close the type definition")
+                .with_label("expected `}`"),
+            ParseError::ExpectedFieldIdentifierError(_) => Diagnostic::error(code, "expected a field identifier")
+                .with_label(unexpected_label(&text)),
+            ParseError::MissingModelTypeError(_) => Diagnostic::error(code, format!("cannot find type `{}`", text))
+                .with_label("not declared in this schema")
+                .with_help(format!("declare it with `type {} {{ ... }}`, `enum {} {{ ... }}` or use a scalar (`Int`, `String`)", text, text)),
+            ParseError::ExpectedEnumVariantError(_) => Diagnostic::error(code, "expected an enum variant")
+                .with_label(unexpected_label(&text)),
+            ParseError::DuplicateEnumVariantError(_) => Diagnostic::error(code, format!("duplicate enum variant `{}`", text))
+                .with_label("variant already declared on this enum")
+                .with_help("rename or remove one of the variants"),
+            ParseError::GenericAnnotationError(_, message) => Diagnostic::error(code, "could not parse annotation")
+                .with_label(unexpected_label(&text))
+                .with_note(message.clone()),
+            ParseError::MissingInverseFieldError(_) => Diagnostic::error(code, format!("cannot find inverse field `{}` on the related type", text))
+                .with_label("not declared on the related type")
+                .with_help("declare the inverse field on the related type, e.g. `posts: [Post] -> author`"),
+            ParseError::InvalidRelationError(_, message) => Diagnostic::error(code, "invalid relation")
+                .with_label(unexpected_label(&text))
+                .with_note(message.clone()),
+            ParseError::GenericError(_) => Diagnostic::error(code, "unexpected token")
+                .with_label(unexpected_label(&text)),
+        };
+
+        match error.span() {
+            Some(span) => diagnostic.with_span(span),
+            None => diagnostic,
+        }
+    }
+
+    // renders the diagnostic like rustc does, with the offending line and an underline
+    pub fn render(&self, file_name: &str, index: &LineIndex) -> String {
+        let mut out = format!("{}[{}]: {}\n", self.severity, self.code, self.message);
+
+        let span = if let Some(s) = self.span {
+            s
+        } else {
+            out.push_str(&format!(" --> {}\n", file_name));
+            self.render_footer(&mut out, " ");
+            return out;
+        };
+
+        let start = index.line_col(span.start);
+        let line = index.line(start.line).unwrap_or("");
+        let gutter = " ".repeat(start.line.to_string().len());
+
+        // underline to the end of the span or the end of the line, whichever comes first
+        let end_col = if span.is_empty() {
+            start.col + 1
+        } else {
+            let end = index.line_col(span.end);
+            if end.line == start.line { end.col } else { line.chars().count() + 1 }
+        };
+        let padding: String = line.chars()
+            .take(start.col - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let underline = "^".repeat(end_col.saturating_sub(start.col).max(1));
+
+        out.push_str(&format!("{}--> {}:{}\n", gutter, file_name, start));
+        out.push_str(&format!("{} |\n", gutter));
+        out.push_str(&format!("{} | {}\n", start.line, line));
+        match &self.label {
+            Some(label) => out.push_str(&format!("{} | {}{} {}\n", gutter, padding, underline, label)),
+            None => out.push_str(&format!("{} | {}{}\n", gutter, padding, underline)),
+        }
+
+        if self.help.is_some() || !self.notes.is_empty() {
+            out.push_str(&format!("{} |\n", gutter));
+        }
+        self.render_footer(&mut out, &gutter);
+
+        out
+    }
+
+    fn render_footer(&self, out: &mut String, gutter: &str) {
+        if let Some(help) = &self.help {
+            out.push_str(&format!("{} = help: {}\n", gutter, help));
+        }
+        for note in self.notes.iter() {
+            out.push_str(&format!("{} = note: {}\n", gutter, note));
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+fn unexpected_label(text: &str) -> String {
+    if text.is_empty() {
+        String::from("unexpected end of file")
+    } else {
+        format!("unexpected `{}`", text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    fn first_error(src: &str) -> String {
+        let mut l = Lexer::new(src);
+        let tokens = l.run();
+        let mut p = Parser::new(&tokens);
+        let error = p.parse().unwrap_err();
+
+        Diagnostic::from_parse_error(&error, tokens.line_index()).render("movie.odet", tokens.line_index())
+    }
+
+    #[test]
+    fn duplicate_field() {
+        let rendered = first_error("type Movie {\n    name: String\n    name: Int\n}");
+
+        assert_eq!(rendered, "\
+error[E0003]: duplicate field identifier `name`
+ --> movie.odet:3:5
+  |
+3 |     name: Int
+  |     ^^^^ field already declared on this type
+  |
+  = help: rename or remove one of the fields
+");
+    }
+
+    #[test]
+    fn missing_model_type() {
+        let rendered = first_error("type Movie {\n\tcomments: [Comment]\n}");
+
+        assert!(rendered.starts_with("error[E0011]: cannot find type `Comment`\n --> movie.odet:2:13\n"));
+        assert!(rendered.contains("2 | \tcomments: [Comment]\n  | \t           ^^^^^^^ not declared"));
+    }
+
+    #[test]
+    fn unexpected_end_of_file() {
+        let rendered = first_error("type Movie");
+
+        assert!(rendered.starts_with("error[E0012]: unexpected token\n --> movie.odet:1:11\n"));
+        assert!(rendered.contains("^ unexpected end of file"));
+    }
+
+    #[test]
+    fn without_span() {
+        let diagnostic = Diagnostic::error("E0001", "unknown parsing error").with_note("something went wrong");
+
+        assert_eq!(diagnostic.render("movie.odet", &LineIndex::new("")), "error[E0001]: unknown parsing error\n --> movie.odet\n  = note: something went wrong\n");
+    }
+}
@@ -0,0 +1,337 @@
+use crate::ast::{Root, ModelTypeDef, EnumDef, FieldDef, FieldTypeType, Annotation, annotations_suffix};
+
+// everything that changed between two schemas, in the order a migration would apply it
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default)]
+pub struct SchemaDiff {
+    pub changes: Vec<Change>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub enum Change {
+    ModelAdded(ModelTypeDef),
+    ModelRemoved(ModelTypeDef),
+    ModelRenamed { from: String, to: String },
+    ModelDocChanged { model: String, from: Option<String>, to: Option<String> },
+    ModelAnnotationsChanged { model: String, from: Vec<Annotation>, to: Vec<Annotation> },
+    FieldAdded { model: String, field: FieldDef },
+    FieldRemoved { model: String, field: FieldDef },
+    FieldChanged { model: String, from: FieldDef, to: FieldDef, changes: Vec<FieldChange> },
+    EnumAdded(EnumDef),
+    EnumRemoved(EnumDef),
+    EnumChanged { from: EnumDef, to: EnumDef, added_variants: Vec<String>, removed_variants: Vec<String> },
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldChange {
+    Type { from: String, to: String },
+    Required { from: bool, to: bool },
+    Array { from: FieldTypeType, to: FieldTypeType },
+    Annotations { from: Vec<Annotation>, to: Vec<Annotation> },
+    Doc { from: Option<String>, to: Option<String> },
+    Relation { from: Option<String>, to: Option<String> }, // name of the inverse field
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    // the diff written as an `.odet` migration file using `+`/`-` markers,
+    // see `Parser::parse_migration`
+    pub fn to_migration(&self) -> String {
+        let mut out = String::new();
+        let mut changed_models: Vec<&str> = Vec::new();
+
+        // group changes on existing models into one block per model
+        for change in self.changes.iter() {
+            let model = match change {
+                Change::ModelRenamed { to, .. } => to,
+                Change::ModelDocChanged { model, .. }
+                | Change::ModelAnnotationsChanged { model, .. }
+                | Change::FieldAdded { model, .. }
+                | Change::FieldRemoved { model, .. }
+                | Change::FieldChanged { model, .. } => model,
+                _ => continue,
+            };
+
+            if !changed_models.contains(&model.as_str()) {
+                changed_models.push(model);
+            }
+        }
+
+        for change in self.changes.iter() {
+            match change {
+                Change::ModelAdded(model) => {
+                    write_doc(&mut out, &model.doc, "");
+                    out.push_str(&format!("+type {}{} {{\n", model.name, annotations_suffix(&model.annotations)));
+                    for field in model.fields.iter() {
+                        write_doc(&mut out, &field.doc, "    ");
+                        out.push_str(&format!("    {}\n", field));
+                    }
+                    out.push_str("}\n\n");
+                },
+                Change::EnumAdded(enum_def) => {
+                    write_doc(&mut out, &enum_def.doc, "");
+                    out.push('+');
+                    write_enum(&mut out, enum_def);
+                },
+                _ => {},
+            }
+        }
+
+        for model in changed_models {
+            self.write_model_changes(&mut out, model);
+        }
+
+        for change in self.changes.iter() {
+            match change {
+                Change::EnumChanged { to, .. } => {
+                    write_doc(&mut out, &to.doc, "");
+                    write_enum(&mut out, to);
+                },
+                Change::ModelRemoved(model) => out.push_str(&format!("-type {}\n\n", model.name)),
+                Change::EnumRemoved(enum_def) => out.push_str(&format!("-enum {}\n\n", enum_def.name)),
+                _ => {},
+            }
+        }
+
+        let trimmed = out.trim_end();
+        if trimmed.is_empty() {
+            String::new()
+        } else {
+            format!("{}\n", trimmed)
+        }
+    }
+
+    fn write_model_changes(&self, out: &mut String, model: &str) {
+        let mut rename = None;
+        let mut doc = None;
+        let mut annotations = Vec::new();
+        // leaving the doc string or the annotations out keeps them, removing them has to be spelled out
+        let mut clear = Vec::new();
+        let mut lines = String::new();
+
+        for change in self.changes.iter() {
+            match change {
+                Change::ModelRenamed { from, to } if to == model => rename = Some(from),
+                Change::ModelDocChanged { model: m, to, .. } if m == model => {
+                    doc = to.clone();
+                    if to.is_none() {
+                        clear.push("doc: true");
+                    }
+                },
+                Change::ModelAnnotationsChanged { model: m, to, .. } if m == model => {
+                    annotations = to.clone();
+                    if to.is_empty() {
+                        clear.push("annotations: true");
+                    }
+                },
+                Change::FieldRemoved { model: m, field } if m == model => {
+                    lines.push_str(&format!("    -{}\n", field.name));
+                },
+                Change::FieldAdded { model: m, field } if m == model => {
+                    write_doc(&mut lines, &field.doc, "    ");
+                    lines.push_str(&format!("    +{}\n", field));
+                },
+                Change::FieldChanged { model: m, to, .. } if m == model => {
+                    write_doc(&mut lines, &to.doc, "    ");
+                    lines.push_str(&format!("    {}\n", to));
+                },
+                _ => {},
+            }
+        }
+
+        write_doc(out, &doc, "");
+        out.push_str(&format!("type {}", model));
+        if let Some(from) = rename {
+            out.push_str(&format!(" @rename(from: \"{}\")", from));
+        }
+        if !clear.is_empty() {
+            out.push_str(&format!(" @clear({})", clear.join(", ")));
+        }
+        out.push_str(&annotations_suffix(&annotations));
+        out.push_str(" {\n");
+        out.push_str(&lines);
+        out.push_str("}\n\n");
+    }
+}
+
+pub fn diff(old: &Root, new: &Root) -> SchemaDiff {
+    let mut changes = Vec::new();
+    let renames = detect_renames(old, new);
+    let renamed_to = |name: &str| -> String {
+        renames.iter()
+            .find(|(from, _)| from == name)
+            .map_or(name.to_string(), |(_, to)| to.clone())
+    };
+
+    for (from, to) in renames.iter() {
+        changes.push(Change::ModelRenamed { from: from.clone(), to: to.clone() });
+    }
+
+    for new_model in new.types.iter() {
+        let old_name = renames.iter()
+            .find(|(_, to)| *to == new_model.name)
+            .map_or(new_model.name.as_str(), |(from, _)| from.as_str());
+
+        let old_model = if let Some(m) = old.model(old_name) {
+            m
+        } else {
+            changes.push(Change::ModelAdded(new_model.clone()));
+            continue;
+        };
+
+        if old_model.doc != new_model.doc {
+            changes.push(Change::ModelDocChanged { model: new_model.name.clone(), from: old_model.doc.clone(), to: new_model.doc.clone() });
+        }
+
+        if old_model.annotations != new_model.annotations {
+            changes.push(Change::ModelAnnotationsChanged { model: new_model.name.clone(), from: old_model.annotations.clone(), to: new_model.annotations.clone() });
+        }
+
+        for old_field in old_model.fields.iter() {
+            if !new_model.fields.iter().any(|f| f.name == old_field.name) {
+                changes.push(Change::FieldRemoved { model: new_model.name.clone(), field: old_field.clone() });
+            }
+        }
+
+        for new_field in new_model.fields.iter() {
+            let old_field = if let Some(f) = old_model.fields.iter().find(|f| f.name == new_field.name) {
+                f
+            } else {
+                changes.push(Change::FieldAdded { model: new_model.name.clone(), field: new_field.clone() });
+                continue;
+            };
+
+            let field_changes = diff_field(old_field, new_field, &renamed_to(old_field.field_type.name()));
+            if !field_changes.is_empty() {
+                changes.push(Change::FieldChanged { model: new_model.name.clone(), from: old_field.clone(), to: new_field.clone(), changes: field_changes });
+            }
+        }
+    }
+
+    for old_model in old.types.iter() {
+        if new.model(&renamed_to(&old_model.name)).is_none() {
+            changes.push(Change::ModelRemoved(old_model.clone()));
+        }
+    }
+
+    for new_enum in new.enums.iter() {
+        let old_enum = if let Some(e) = old.enum_def(&new_enum.name) {
+            e
+        } else {
+            changes.push(Change::EnumAdded(new_enum.clone()));
+            continue;
+        };
+
+        let added_variants: Vec<String> = new_enum.variants.iter()
+            .filter(|v| !old_enum.variants.iter().any(|o| o.name == v.name))
+            .map(|v| v.name.clone())
+            .collect();
+        let removed_variants: Vec<String> = old_enum.variants.iter()
+            .filter(|v| !new_enum.variants.iter().any(|n| n.name == v.name))
+            .map(|v| v.name.clone())
+            .collect();
+        let variant_docs_changed = new_enum.variants.iter()
+            .any(|v| old_enum.variants.iter().any(|o| o.name == v.name && o.doc != v.doc));
+
+        if !added_variants.is_empty() || !removed_variants.is_empty() || variant_docs_changed
+            || old_enum.doc != new_enum.doc || old_enum.annotations != new_enum.annotations {
+            changes.push(Change::EnumChanged { from: old_enum.clone(), to: new_enum.clone(), added_variants, removed_variants });
+        }
+    }
+
+    for old_enum in old.enums.iter() {
+        if new.enum_def(&old_enum.name).is_none() {
+            changes.push(Change::EnumRemoved(old_enum.clone()));
+        }
+    }
+
+    SchemaDiff { changes }
+}
+
+// `old_type` is the old field type with model renames already applied
+fn diff_field(old: &FieldDef, new: &FieldDef, old_type: &str) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    if old_type != new.field_type.name() {
+        changes.push(FieldChange::Type { from: old.field_type.name().to_string(), to: new.field_type.name().to_string() });
+    }
+
+    if old.required != new.required {
+        changes.push(FieldChange::Required { from: old.required, to: new.required });
+    }
+
+    if old.type_type != new.type_type {
+        changes.push(FieldChange::Array { from: old.type_type.clone(), to: new.type_type.clone() });
+    }
+
+    if old.annotations != new.annotations {
+        changes.push(FieldChange::Annotations { from: old.annotations.clone(), to: new.annotations.clone() });
+    }
+
+    if old.doc != new.doc {
+        changes.push(FieldChange::Doc { from: old.doc.clone(), to: new.doc.clone() });
+    }
+
+    let old_inverse = old.relation.as_ref().map(|r| r.inverse.clone());
+    let new_inverse = new.relation.as_ref().map(|r| r.inverse.clone());
+    if old_inverse != new_inverse {
+        changes.push(FieldChange::Relation { from: old_inverse, to: new_inverse });
+    }
+
+    changes
+}
+
+// a model that disappeared and a new one with exactly the same fields is treated as a rename
+fn detect_renames(old: &Root, new: &Root) -> Vec<(String, String)> {
+    let mut renames: Vec<(String, String)> = Vec::new();
+
+    for removed in old.types.iter().filter(|m| new.model(&m.name).is_none()) {
+        let candidate = new.types.iter()
+            .filter(|m| old.model(&m.name).is_none())
+            .filter(|m| !renames.iter().any(|(_, to)| *to == m.name))
+            .find(|m| same_shape(removed, m));
+
+        if let Some(added) = candidate {
+            renames.push((removed.name.clone(), added.name.clone()));
+        }
+    }
+
+    renames
+}
+
+fn same_shape(old: &ModelTypeDef, new: &ModelTypeDef) -> bool {
+    old.fields.len() == new.fields.len()
+        && old.fields.iter().zip(new.fields.iter()).all(|(o, n)| {
+            // self references follow the rename
+            let old_type = if o.field_type.name() == old.name { new.name.as_str() } else { o.field_type.name() };
+
+            o.name == n.name
+                && old_type == n.field_type.name()
+                && o.required == n.required
+                && o.type_type == n.type_type
+        })
+}
+
+fn write_doc(out: &mut String, doc: &Option<String>, indent: &str) {
+    if let Some(doc) = doc {
+        out.push_str(&format!("{}\"\"\"\n", indent));
+        for line in doc.lines() {
+            out.push_str(&format!("{}{}\n", indent, line.trim()));
+        }
+        out.push_str(&format!("{}\"\"\"\n", indent));
+    }
+}
+
+fn write_enum(out: &mut String, enum_def: &EnumDef) {
+    out.push_str(&format!("enum {}{} {{\n", enum_def.name, annotations_suffix(&enum_def.annotations)));
+    for variant in enum_def.variants.iter() {
+        write_doc(out, &variant.doc, "    ");
+        out.push_str(&format!("    {}\n", variant.name));
+    }
+    out.push_str("}\n\n");
+}
@@ -0,0 +1,186 @@
+use crate::{
+    ast::{Root, ModelTypeDef, EnumDef, annotations_suffix},
+    lexer::Tokens,
+    codegen::doc_lines,
+};
+
+const INDENT: &str = "    ";
+
+// a `#` comment, whether code comes before it on the same line and whether a blank line follows it
+struct Comment {
+    text: String,
+    start: usize,
+    trailing: bool,
+    blank_after: bool,
+}
+
+enum Definition<'a> {
+    Model(&'a ModelTypeDef),
+    Enum(&'a EnumDef),
+}
+
+impl Definition<'_> {
+    fn start(&self) -> usize {
+        match self {
+            Definition::Model(m) => m.span.start,
+            Definition::Enum(e) => e.span.start,
+        }
+    }
+}
+
+// canonical `.odet` source for a parsed schema, keeping the comments of the file it came from.
+// comments stay in front of the definition, field or variant that follows them or at the end
+// of the line they were written on. blank lines are dropped except after a comment
+pub fn format(root: &Root, tokens: &Tokens) -> String {
+    let line = |offset: usize| tokens.line_index().line_col(offset).line;
+
+    let comments = tokens.comments().iter()
+        .map(|c| {
+            let before = tokens.into_iter().take_while(|t| t.span.end <= c.span.start).last();
+
+            Comment {
+                text: c.value.clone(),
+                start: c.span.start,
+                trailing: before.is_some_and(|t| line(t.span.end) == line(c.span.start)),
+                blank_after: tokens.line_index().line(line(c.span.start) + 1).is_some_and(|l| l.trim().is_empty()),
+            }
+        })
+        .collect();
+
+    Printer { comments, next: 0, out: String::new() }.root(root)
+}
+
+// canonical `.odet` source for a schema that doesn't come from a file, e.g. an imported one
+pub fn print(root: &Root) -> String {
+    Printer { comments: Vec::new(), next: 0, out: String::new() }.root(root)
+}
+
+struct Printer {
+    comments: Vec<Comment>,
+    next: usize,
+    out: String,
+}
+
+impl Printer {
+    fn root(mut self, root: &Root) -> String {
+        // models and enums are kept apart in the ast, the source order decides
+        let mut definitions: Vec<Definition> = root.types.iter().map(Definition::Model)
+            .chain(root.enums.iter().map(Definition::Enum))
+            .collect();
+        definitions.sort_by_key(|d| d.start());
+
+        for (i, definition) in definitions.iter().enumerate() {
+            let end = definitions.get(i + 1).map_or(usize::MAX, |d| d.start());
+
+            if i > 0 {
+                self.out.push('\n');
+            }
+            match definition {
+                Definition::Model(m) => self.model(m, end),
+                Definition::Enum(e) => self.enum_def(e, end),
+            }
+        }
+
+        if self.next < self.comments.len() && !definitions.is_empty() {
+            self.out.push('\n');
+        }
+        self.leading(usize::MAX, "", false);
+
+        self.out
+    }
+
+    fn model(&mut self, model: &ModelTypeDef, end: usize) {
+        let close = model.span.end.saturating_sub(1);
+
+        self.leading(model.span.start, "", true);
+        self.doc(&model.doc, "");
+        self.out.push_str(&format!("type {}{} {{", model.name, annotations_suffix(&model.annotations)));
+
+        if model.fields.is_empty() && !self.pending(close) {
+            self.out.push('}');
+        } else {
+            self.trailing(model.fields.first().map_or(close, |f| f.span.start));
+
+            for (i, field) in model.fields.iter().enumerate() {
+                self.leading(field.span.start, INDENT, true);
+                self.doc(&field.doc, INDENT);
+                self.out.push_str(&format!("{}{}", INDENT, field));
+                self.trailing(model.fields.get(i + 1).map_or(close, |f| f.span.start));
+            }
+
+            self.leading(close, INDENT, false);
+            self.out.push('}');
+        }
+
+        self.trailing(end);
+    }
+
+    // one variant per line, like fields
+    fn enum_def(&mut self, enum_def: &EnumDef, end: usize) {
+        let close = enum_def.span.end.saturating_sub(1);
+
+        self.leading(enum_def.span.start, "", true);
+        self.doc(&enum_def.doc, "");
+        self.out.push_str(&format!("enum {}{} {{", enum_def.name, annotations_suffix(&enum_def.annotations)));
+
+        if enum_def.variants.is_empty() && !self.pending(close) {
+            self.out.push('}');
+        } else {
+            self.trailing(enum_def.variants.first().map_or(close, |v| v.span.start));
+
+            for (i, variant) in enum_def.variants.iter().enumerate() {
+                self.leading(variant.span.start, INDENT, true);
+                self.doc(&variant.doc, INDENT);
+                self.out.push_str(&format!("{}{}", INDENT, variant.name));
+                self.trailing(enum_def.variants.get(i + 1).map_or(close, |v| v.span.start));
+            }
+
+            self.leading(close, INDENT, false);
+            self.out.push('}');
+        }
+
+        self.trailing(end);
+    }
+
+    // doc strings always use the block form with every line re-indented
+    fn doc(&mut self, doc: &Option<String>, indent: &str) {
+        if let Some(doc) = doc {
+            self.out.push_str(&format!("{}\"\"\"\n", indent));
+            for line in doc_lines(doc) {
+                if line.is_empty() {
+                    self.out.push('\n');
+                } else {
+                    self.out.push_str(&format!("{}{}\n", indent, line));
+                }
+            }
+            self.out.push_str(&format!("{}\"\"\"\n", indent));
+        }
+    }
+
+    fn pending(&self, before: usize) -> bool {
+        self.comments.get(self.next).is_some_and(|c| c.start < before)
+    }
+
+    // comments written before `before` on lines of their own, `separate` keeps a blank line
+    // between a comment and whatever is printed after it
+    fn leading(&mut self, before: usize, indent: &str, separate: bool) {
+        while self.pending(before) {
+            let comment = &self.comments[self.next];
+            self.out.push_str(&format!("{}{}\n", indent, comment.text));
+            self.next += 1;
+
+            if comment.blank_after && (separate || self.pending(before)) {
+                self.out.push('\n');
+            }
+        }
+    }
+
+    // ends the current line, with the comment that followed it in the source if there was one
+    fn trailing(&mut self, before: usize) {
+        if self.pending(before) && self.comments[self.next].trailing {
+            self.out.push_str(&format!(" {}", self.comments[self.next].text));
+            self.next += 1;
+        }
+        self.out.push('\n');
+    }
+}
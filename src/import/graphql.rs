@@ -0,0 +1,491 @@
+use crate::{
+    _helpers::{ImportError, ImportResult},
+    ast::{Root, ModelTypeDef, EnumDef, EnumVariant, FieldDef, FieldType, FieldTypeType, Annotation, AnnotationArg, AnnotationValue},
+    span::Span,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Name(String),
+    Punct(char),
+    Str(String), // both `"..."` and `"""..."""`, already unescaped
+    Int(i64),
+    Float(f64),
+    Variable(String),
+    Eof,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    t: Tok,
+    span: Span,
+}
+
+// reads a graphql sdl document into a schema. object types become models, enums stay enums,
+// descriptions become doc strings and directives become annotations.
+// `ID` is imported as `String @id`. the root operation types (`Query`, `Mutation`, `Subscription`
+// or the ones named in `schema { ... }`) are skipped as they describe an api, not data
+pub fn import(src: &str) -> ImportResult<Root> {
+    let mut importer = Importer { tokens: tokenize(src)?, pos: 0 };
+    let mut models: Vec<(ModelTypeDef, Option<Span>)> = Vec::new(); // with the span of the first field argument
+    let mut enums = Vec::new();
+    let mut operation_types = vec![String::from("Query"), String::from("Mutation"), String::from("Subscription")];
+
+    loop {
+        let doc = importer.description();
+        let token = importer.peek().clone();
+
+        match &token.t {
+            Tok::Eof if doc.is_none() => break,
+            Tok::Name(keyword) if keyword == "type" => models.push(importer.object_type(doc)?),
+            Tok::Name(keyword) if keyword == "enum" => enums.push(importer.enum_type(doc)?),
+            Tok::Name(keyword) if keyword == "schema" => operation_types.extend(importer.schema()?),
+            Tok::Name(keyword) => {
+                let construct = match keyword.as_str() {
+                    "interface" => "Interfaces",
+                    "union" => "Unions",
+                    "input" => "Input types",
+                    "scalar" => "Custom scalars",
+                    "directive" => "Directive definitions",
+                    "extend" => "Type extensions",
+                    _ => return Err(ImportError::SyntaxError(String::from("a type definition"), token.span)),
+                };
+                return Err(ImportError::UnsupportedError(String::from(construct), token.span));
+            },
+            _ => return Err(ImportError::SyntaxError(String::from("a type definition"), token.span)),
+        }
+    }
+
+    let mut root = Root::new();
+    for (model, argument) in models {
+        if operation_types.contains(&model.name) {
+            continue;
+        }
+        if let Some(span) = argument {
+            return Err(ImportError::UnsupportedError(String::from("Field arguments"), span));
+        }
+        if root.types.iter().any(|m| m.name == model.name) || enums.iter().any(|e: &EnumDef| e.name == model.name) {
+            return Err(ImportError::DuplicateTypeError(model.name.clone(), model.name_span));
+        }
+        root.types.push(model);
+    }
+    for enum_def in enums {
+        if root.enums.iter().any(|e| e.name == enum_def.name) {
+            return Err(ImportError::DuplicateTypeError(enum_def.name.clone(), enum_def.name_span));
+        }
+        root.enums.push(enum_def);
+    }
+
+    resolve_types(&mut root, &operation_types)?;
+    Ok(root)
+}
+
+// turns the raw type names into scalars and references now that every type is known
+fn resolve_types(root: &mut Root, operation_types: &[String]) -> ImportResult<()> {
+    let names: Vec<String> = root.types.iter().map(|m| m.name.clone())
+        .chain(root.enums.iter().map(|e| e.name.clone()))
+        .collect();
+
+    for model in root.types.iter_mut() {
+        for field in model.fields.iter_mut() {
+            let (name, span) = (field.field_type.name().to_string(), field.field_type.span());
+
+            field.field_type = match name.as_str() {
+                "Int" | "String" => FieldType::Scalar(name, span),
+                "ID" => {
+                    field.annotations.insert(0, Annotation { name: String::from("id"), args: Vec::new(), span });
+                    FieldType::Scalar(String::from("String"), span)
+                },
+                "Float" | "Boolean" => return Err(ImportError::UnsupportedError(format!("The `{}` scalar", name), span)),
+                _ if names.contains(&name) => FieldType::Identfier(name, span),
+                _ if operation_types.contains(&name) => return Err(ImportError::UnsupportedError(format!("A reference to the operation type `{}`", name), span)),
+                _ => return Err(ImportError::UnknownTypeError(name, span)),
+            };
+        }
+    }
+
+    Ok(())
+}
+
+struct Importer {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Importer {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn bump(&mut self) -> Token {
+        let token = self.peek().clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn prev_span(&self) -> Span {
+        self.tokens[self.pos.saturating_sub(1)].span
+    }
+
+    fn at(&self, c: char) -> bool {
+        self.peek().t == Tok::Punct(c)
+    }
+
+    fn expect(&mut self, c: char) -> ImportResult<Token> {
+        if self.at(c) {
+            Ok(self.bump())
+        } else {
+            Err(ImportError::SyntaxError(format!("`{}`", c), self.peek().span))
+        }
+    }
+
+    fn name(&mut self, what: &str) -> ImportResult<(String, Span)> {
+        let token = self.bump();
+        match token.t {
+            Tok::Name(name) => Ok((name, token.span)),
+            _ => Err(ImportError::SyntaxError(String::from(what), token.span)),
+        }
+    }
+
+    fn description(&mut self) -> Option<String> {
+        if let Tok::Str(s) = &self.peek().t {
+            let doc = s.trim().to_string();
+            self.bump();
+            if !doc.is_empty() {
+                return Some(doc);
+            }
+        }
+        None
+    }
+
+    fn object_type(&mut self, doc: Option<String>) -> ImportResult<(ModelTypeDef, Option<Span>)> {
+        let start = self.bump().span;
+        let (name, name_span) = self.name("a type name")?;
+
+        if self.peek().t == Tok::Name(String::from("implements")) {
+            return Err(ImportError::UnsupportedError(String::from("Interfaces"), self.peek().span));
+        }
+
+        let annotations = self.directives()?;
+        let mut fields: Vec<FieldDef> = Vec::new();
+        let mut argument = None;
+
+        if !self.at('{') {
+            return Err(ImportError::UnsupportedError(String::from("Types without fields"), name_span));
+        }
+        self.bump();
+
+        while !self.at('}') {
+            let doc = self.description();
+            let (field_name, field_span) = self.name("a field name or `}`")?;
+
+            if self.at('(') {
+                argument.get_or_insert(self.peek().span);
+                self.skip_group('(', ')')?;
+            }
+            self.expect(':')?;
+
+            let (type_name, type_span, type_type, required) = self.field_type()?;
+            let annotations = self.directives()?;
+
+            // keywords can't be field names in `.odet` files
+            if field_name == "type" || field_name == "enum" {
+                return Err(ImportError::UnsupportedError(format!("A field called `{}`", field_name), field_span));
+            }
+            if fields.iter().any(|f| f.name == field_name) {
+                return Err(ImportError::DuplicateFieldError(name.clone(), field_name, field_span));
+            }
+            fields.push(FieldDef {
+                name: field_name,
+                field_type: FieldType::Identfier(type_name, type_span), // resolved once all types are known
+                type_type,
+                required,
+                doc,
+                annotations,
+                relation: None,
+                span: field_span.to(self.prev_span()),
+                name_span: field_span,
+            });
+        }
+        self.bump();
+
+        if fields.is_empty() {
+            return Err(ImportError::UnsupportedError(String::from("Types without fields"), name_span));
+        }
+
+        let model = ModelTypeDef { name, fields, doc, annotations, span: start.to(self.prev_span()), name_span };
+        Ok((model, argument))
+    }
+
+    // `T`, `T!`, `[T]`, `[T!]` with an optional outer `!`
+    fn field_type(&mut self) -> ImportResult<(String, Span, FieldTypeType, bool)> {
+        let (name, span, type_type) = if self.at('[') {
+            self.bump();
+            if self.at('[') {
+                return Err(ImportError::UnsupportedError(String::from("Nested lists"), self.peek().span));
+            }
+
+            let (name, span) = self.name("a type name")?;
+            let type_type = if self.at('!') {
+                self.bump();
+                FieldTypeType::RequiredArray
+            } else {
+                FieldTypeType::Array
+            };
+            self.expect(']')?;
+            (name, span, type_type)
+        } else {
+            let (name, span) = self.name("a type")?;
+            (name, span, FieldTypeType::Basic)
+        };
+
+        let required = self.at('!');
+        if required {
+            self.bump();
+        }
+
+        Ok((name, span, type_type, required))
+    }
+
+    fn enum_type(&mut self, doc: Option<String>) -> ImportResult<EnumDef> {
+        let start = self.bump().span;
+        let (name, name_span) = self.name("an enum name")?;
+        let annotations = self.directives()?;
+        let mut variants: Vec<EnumVariant> = Vec::new();
+
+        self.expect('{')?;
+        while !self.at('}') {
+            let doc = self.description();
+            let (variant, span) = self.name("an enum value or `}`")?;
+            if !self.directives()?.is_empty() {
+                return Err(ImportError::UnsupportedError(String::from("Directives on enum values"), span.to(self.prev_span())));
+            }
+            if variants.iter().any(|v| v.name == variant) {
+                return Err(ImportError::DuplicateFieldError(name.clone(), variant, span));
+            }
+            variants.push(EnumVariant { name: variant, doc, span });
+        }
+        self.bump();
+
+        Ok(EnumDef { name, variants, doc, annotations, span: start.to(self.prev_span()), name_span })
+    }
+
+    // `schema { query: Root }`, returns the operation type names
+    fn schema(&mut self) -> ImportResult<Vec<String>> {
+        self.bump();
+        self.directives()?;
+        self.expect('{')?;
+
+        let mut names = Vec::new();
+        while !self.at('}') {
+            self.name("an operation type or `}`")?;
+            self.expect(':')?;
+            names.push(self.name("a type name")?.0);
+        }
+        self.bump();
+
+        Ok(names)
+    }
+
+    fn directives(&mut self) -> ImportResult<Vec<Annotation>> {
+        let mut annotations = Vec::new();
+
+        while self.at('@') {
+            let start = self.bump().span;
+            let (name, _) = self.name("a directive name")?;
+            let mut args = Vec::new();
+
+            if self.at('(') {
+                self.bump();
+                while !self.at(')') {
+                    let (arg, _) = self.name("an argument name or `)`")?;
+                    self.expect(':')?;
+                    args.push(AnnotationArg { name: arg, value: self.value()? });
+                }
+                self.bump();
+            }
+
+            annotations.push(Annotation { name, args, span: start.to(self.prev_span()) });
+        }
+
+        Ok(annotations)
+    }
+
+    fn value(&mut self) -> ImportResult<AnnotationValue> {
+        let token = self.bump();
+
+        match token.t {
+            Tok::Int(i) => Ok(AnnotationValue::Int(i)),
+            Tok::Float(n) => Ok(AnnotationValue::Float(n)),
+            Tok::Str(s) => Ok(AnnotationValue::String(s)),
+            Tok::Name(n) if n == "true" || n == "false" => Ok(AnnotationValue::Bool(n == "true")),
+            Tok::Name(n) if n == "null" => Err(ImportError::UnsupportedError(String::from("`null` as a directive argument"), token.span)),
+            Tok::Name(n) => Ok(AnnotationValue::Identifier(n)),
+            Tok::Variable(_) => Err(ImportError::UnsupportedError(String::from("Variables as directive arguments"), token.span)),
+            Tok::Punct('{') => Err(ImportError::UnsupportedError(String::from("Objects as directive arguments"), token.span)),
+            Tok::Punct('[') => {
+                let mut values = Vec::new();
+                while !self.at(']') {
+                    values.push(self.value()?);
+                }
+                self.bump();
+                Ok(AnnotationValue::List(values))
+            },
+            _ => Err(ImportError::SyntaxError(String::from("a value"), token.span)),
+        }
+    }
+
+    // skips balanced brackets, used for field arguments which are reported later
+    fn skip_group(&mut self, open: char, close: char) -> ImportResult<()> {
+        let mut depth = 0;
+
+        loop {
+            let token = self.bump();
+            match token.t {
+                Tok::Punct(c) if c == open => depth += 1,
+                Tok::Punct(c) if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                },
+                Tok::Eof => return Err(ImportError::SyntaxError(format!("`{}`", close), token.span)),
+                _ => {},
+            }
+        }
+    }
+}
+
+fn tokenize(src: &str) -> ImportResult<Vec<Token>> {
+    let chars: Vec<(usize, char)> = src.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map_or(src.len(), |(o, _)| *o);
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (start, c) = chars[i];
+
+        // commas are insignificant in graphql
+        if c.is_whitespace() || c == ',' || c == '\u{feff}' {
+            i += 1;
+            continue;
+        }
+
+        if c == '#' {
+            while i < chars.len() && chars[i].1 != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+
+        let t = if c.is_ascii_alphabetic() || c == '_' || c == '$' {
+            let from = if c == '$' { i + 1 } else { i };
+            i += 1;
+            while i < chars.len() && (chars[i].1.is_ascii_alphanumeric() || chars[i].1 == '_') {
+                i += 1;
+            }
+
+            let name = src[offset(from)..offset(i)].to_string();
+            if c == '$' { Tok::Variable(name) } else { Tok::Name(name) }
+        } else if c.is_ascii_digit() || c == '-' {
+            i += 1;
+            while i < chars.len() && (chars[i].1.is_ascii_digit() || ".eE+-".contains(chars[i].1)) {
+                i += 1;
+            }
+
+            let text = &src[start..offset(i)];
+            if let Ok(n) = text.parse::<i64>() {
+                Tok::Int(n)
+            } else if let Ok(n) = text.parse::<f64>() {
+                Tok::Float(n)
+            } else {
+                return Err(ImportError::SyntaxError(String::from("a number"), Span::new(start, offset(i))));
+            }
+        } else if src[start..].starts_with("\"\"\"") {
+            let body_start = start + 3;
+            let end = src[body_start..].match_indices("\"\"\"")
+                .find(|(at, _)| !src[..body_start + at].ends_with('\\'))
+                .map(|(at, _)| body_start + at)
+                .ok_or_else(|| ImportError::SyntaxError(String::from("the end of the block string"), Span::new(start, src.len())))?;
+
+            while offset(i) < end + 3 {
+                i += 1;
+            }
+            Tok::Str(block_string(&src[body_start..end]))
+        } else if c == '"' {
+            let mut value = String::new();
+            i += 1;
+
+            loop {
+                let (at, c) = match chars.get(i) {
+                    Some(&(_, '\n')) | None => return Err(ImportError::SyntaxError(String::from("the end of the string"), Span::new(start, offset(i)))),
+                    Some(&pair) => pair,
+                };
+                i += 1;
+
+                match c {
+                    '"' => break,
+                    '\\' => {
+                        let escaped = chars.get(i).map(|(_, e)| *e);
+                        i += 1;
+                        match escaped {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some('r') => value.push('\r'),
+                            Some('b') => value.push('\u{8}'),
+                            Some('f') => value.push('\u{c}'),
+                            Some('u') => {
+                                let hex = src.get(offset(i)..offset(i + 4)).unwrap_or("");
+                                let unicode = u32::from_str_radix(hex, 16).ok().and_then(std::char::from_u32)
+                                    .ok_or_else(|| ImportError::SyntaxError(String::from("a unicode escape"), Span::new(at, offset(i + 4))))?;
+                                value.push(unicode);
+                                i += 4;
+                            },
+                            Some(e @ ('"' | '\\' | '/')) => value.push(e),
+                            _ => return Err(ImportError::SyntaxError(String::from("a valid escape sequence"), Span::new(at, offset(i)))),
+                        }
+                    },
+                    c => value.push(c),
+                }
+            }
+
+            Tok::Str(value)
+        } else if "{}()[]:!=@|&".contains(c) {
+            i += 1;
+            Tok::Punct(c)
+        } else {
+            return Err(ImportError::SyntaxError(String::from("a name, a value or punctuation"), Span::new(start, start + c.len_utf8())));
+        };
+
+        tokens.push(Token { t, span: Span::new(start, offset(i)) });
+    }
+
+    tokens.push(Token { t: Tok::Eof, span: Span::new(src.len(), src.len()) });
+    Ok(tokens)
+}
+
+// removes the common indentation and the blank first and last lines, as the graphql spec does
+fn block_string(raw: &str) -> String {
+    let raw = raw.replace("\\\"\"\"", "\"\"\"");
+    let lines: Vec<&str> = raw.lines().collect();
+
+    let indent = lines.iter().skip(1)
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut lines: Vec<&str> = lines.iter().enumerate()
+        .map(|(i, l)| if i == 0 { l } else { l.get(indent..).unwrap_or("") })
+        .collect();
+    while lines.first().is_some_and(|l| l.trim().is_empty()) {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
@@ -2,12 +2,15 @@ use std::iter::{Peekable};
 use std::str::{Chars};
 use std::fmt;
 
+use crate::span::{Span, LineIndex};
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenType {
     Unknown,
     Identifier,
 
     FieldType,
+    Enum,
 
     TInt,
     TString,
@@ -27,7 +30,13 @@ pub enum TokenType {
     BracketR,
 
     Colon,
+    Comma,
+    At,
     DocString,
+    StringLiteral,
+    Number,
+    Comment, // only found in `Tokens::comments` and `Tokens::trivia`, never in the token stream
+    Whitespace, // only found in `Tokens::trivia`
 
     EOF,
 }
@@ -36,7 +45,7 @@ pub enum TokenType {
 pub struct Token {
     pub t: TokenType,
     pub value: String,
-    pub loc: (usize, usize) // @todo look into getting col and row for location
+    pub span: Span,
 }
 
 impl Token {
@@ -44,15 +53,14 @@ impl Token {
         Token {
             t: TokenType::EOF,
             value: "".to_string(),
-            loc: (index, index)
+            span: Span::new(index, index)
         }
     }
 }
 
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        
-        write!(f, "{:?} : ({}, {}), value = {}\n", self.t, self.loc.0, self.loc.1, self.value)
+        writeln!(f, "{:?} : {}, value = {}", self.t, self.span, self.value)
     }
 }
 
@@ -62,23 +70,42 @@ pub struct TokenIter<'a> {
 
 #[derive(Clone, Debug)]
 pub struct Tokens {
-    tokens: Vec<Token>
+    tokens: Vec<Token>,
+    comments: Vec<Token>,
+    trivia: Vec<Token>,
+    line_index: LineIndex,
+}
+
+impl Tokens {
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
+    // `# ...` comments in source order, the parser never sees them
+    pub fn comments(&self) -> &[Token] {
+        &self.comments
+    }
+
+    // everything between the tokens in source order, only filled in by `Lexer::lossless`.
+    // the value of trivia is the source text as written
+    pub fn trivia(&self) -> &[Token] {
+        &self.trivia
+    }
 }
 
 impl fmt::Display for Tokens {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let vec = &self.tokens;
 
-        write!(f, "[\n")?;
-        for (_, v) in vec.iter().enumerate() {
+        writeln!(f, "[")?;
+        for v in vec.iter() {
             let mut value = String::new();
-            if v.value.len() > 0 {
+            if !v.value.is_empty() {
                 value = format!(", value = '{}'", v.value);
             }
-            write!(f, "\t{:?}: ({}, {}){}\n", v.t, v.loc.0, v.loc.1, value)?;
+            writeln!(f, "\t{:?}: {} ({}){}", v.t, self.line_index.line_col(v.span.start), v.span, value)?;
         }
-        write!(f, "]\n")
-        
+        writeln!(f, "]")
     }
 }
 
@@ -106,14 +133,27 @@ pub struct Lexer<'a> {
     orginal: &'a str,
     chars: Peekable<Chars<'a>>,
     index: usize,
+    comments: Vec<Token>,
+    trivia: Option<Vec<Token>>,
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(src: &str) -> Lexer {
+    pub fn new(src: &str) -> Lexer<'_> {
         Lexer {
             orginal: src,
             chars: src.chars().peekable(),
             index: 0,
+            comments: Vec::new(),
+            trivia: None,
+        }
+    }
+
+    // also keeps whitespace, comments and skipped doc strings as trivia,
+    // together with the tokens they cover every byte of the source
+    pub fn lossless(src: &str) -> Lexer<'_> {
+        Lexer {
+            trivia: Some(Vec::new()),
+            ..Lexer::new(src)
         }
     }
 
@@ -131,7 +171,12 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        Tokens { tokens }
+        Tokens {
+            tokens,
+            comments: std::mem::take(&mut self.comments),
+            trivia: self.trivia.take().unwrap_or_default(),
+            line_index: LineIndex::new(self.orginal),
+        }
     }
 
     fn next(&mut self) -> Token {
@@ -141,54 +186,39 @@ impl<'a> Lexer<'a> {
             return Token::eof(self.index);
         };
 
-        while WHITESPACE.contains(&c) {
-            let next_char = self.advance();
-            if next_char == None || self.peek() == None {
-                return Token::eof(self.index);
+        // skip whitespace and comments until something meaningful, comments are kept as trivia
+        loop {
+            if WHITESPACE.contains(&c) {
+                self.next_whitespace();
+            } else if c == '#' {
+                self.next_comment();
+            } else {
+                break;
             }
 
-            c = *self.peek().unwrap();
+            c = if let Some(c) = self.peek() {
+                *c
+            } else {
+                return Token::eof(self.index);
+            };
         }
 
         if c == '"' {
+            let start = self.index;
             if let Some(co) = self.next_doc_string() {
                 return co;
             }
-            // look for string Identifier here later?
-            let next_char = self.advance();
-            if next_char == None || self.peek() == None {
-                return Token::eof(self.index);
+            // an empty doc string is skipped entirely
+            if self.index != start {
+                self.push_trivia(TokenType::DocString, start);
+                return self.next();
             }
 
-            c = *self.peek().unwrap();
-            while WHITESPACE.contains(&c) {
-                let next_char = self.advance();
-                if next_char == None || self.peek() == None {
-                    return Token::eof(self.index);
-                }
-
-                c = *self.peek().unwrap();
-            }
+            return self.next_string_literal();
         }
 
-        if c == '#' {
-            while !NEW_LINE.contains(&c)  {
-                let next_char = self.advance();
-                if next_char == None || self.peek() == None {
-                    return Token::eof(self.index);
-                }
-
-                c = *self.peek().unwrap();
-            }
-
-            while WHITESPACE.contains(&c) {
-                let next_char = self.advance();
-                if next_char == None || self.peek() == None {
-                    return Token::eof(self.index);
-                }
-
-                c = *self.peek().unwrap();
-            }
+        if c.is_ascii_digit() {
+            return self.next_number();
         }
 
         if is_special_identifier(Some(&c)) {
@@ -201,21 +231,23 @@ impl<'a> Lexer<'a> {
             return self.next_identifier();
         }
 
-        let loc = (self.index, self.index + 1);
+        let span = Span::new(self.index, self.index + c.len_utf8());
         let token = match c {
-            '+' => Token { t: TokenType::OpPlus, value: String::new(), loc },
-            '-' => Token { t: TokenType::OpMinus, value: String::new(), loc },
-            '*' => Token { t: TokenType::OpStar, value: String::new(), loc },
-            '/' => Token { t: TokenType::OpForwSlash, value: String::new(), loc },
-            '!' => Token { t: TokenType::OpExclamation, value: String::new(), loc },
-            '(' => Token { t: TokenType::ParenL, value: String::new(), loc },
-            ')' => Token { t: TokenType::ParenR, value: String::new(), loc },
-            '{' => Token { t: TokenType::CurlyL, value: String::new(), loc },
-            '}' => Token { t: TokenType::CurlyR, value: String::new(), loc },
-            '[' => Token { t: TokenType::BracketL, value: String::new(), loc },
-            ']' => Token { t: TokenType::BracketR, value: String::new(), loc },
-            ':' => Token { t: TokenType::Colon, value: String::new(), loc },
-            _ => Token { t: TokenType::Unknown, value: c.to_string(), loc }
+            '+' => Token { t: TokenType::OpPlus, value: String::new(), span },
+            '-' => Token { t: TokenType::OpMinus, value: String::new(), span },
+            '*' => Token { t: TokenType::OpStar, value: String::new(), span },
+            '/' => Token { t: TokenType::OpForwSlash, value: String::new(), span },
+            '!' => Token { t: TokenType::OpExclamation, value: String::new(), span },
+            '(' => Token { t: TokenType::ParenL, value: String::new(), span },
+            ')' => Token { t: TokenType::ParenR, value: String::new(), span },
+            '{' => Token { t: TokenType::CurlyL, value: String::new(), span },
+            '}' => Token { t: TokenType::CurlyR, value: String::new(), span },
+            '[' => Token { t: TokenType::BracketL, value: String::new(), span },
+            ']' => Token { t: TokenType::BracketR, value: String::new(), span },
+            ':' => Token { t: TokenType::Colon, value: String::new(), span },
+            ',' => Token { t: TokenType::Comma, value: String::new(), span },
+            '@' => Token { t: TokenType::At, value: String::new(), span },
+            _ => Token { t: TokenType::Unknown, value: c.to_string(), span }
         };
 
         self.advance();
@@ -228,45 +260,50 @@ impl<'a> Lexer<'a> {
     }
 
     fn advance(&mut self) -> Option<char> {
-        self.index += 1;
-        self.chars.next()
+        let next = self.chars.next();
+        if let Some(c) = next {
+            self.index += c.len_utf8();
+        }
+        next
     }
 
+    // rewind to a byte offset that was previously reached
     fn back(&mut self, index: usize) {
-        self.index = index - 1;
-        self.chars = self.orginal.chars().peekable();
-        self.chars.nth(index - 1);
+        self.index = index;
+        self.chars = self.orginal[index..].chars().peekable();
     }
 
     fn next_identifier(&mut self) -> Token {
         let start = self.index;
         let mut end = start;
-        let letter_regex = regex::Regex::new(r"[A-z'_]").unwrap();
+        let letter_regex = regex::Regex::new(r"[A-Za-z0-9_]").unwrap();
         let mut value = String::new();
 
-        while is_valid_identifier(self.peek()) {
+        // digits can't start an identifier, that's a number, but can follow the first letter
+        while is_valid_identifier(self.peek()) || (end > start && self.peek().is_some_and(|c| c.is_ascii_digit())) {
             let c = *self.peek().unwrap();
 
             if end - start > 0 && !letter_regex.is_match(&c.to_string()) {
                 break;
             }
 
-            end += 1;
+            end += c.len_utf8();
             value.push(c);
             self.advance();
         }
 
         let t = match value.as_ref() {
             "type" => TokenType::FieldType,
+            "enum" => TokenType::Enum,
             "Int" => TokenType::TInt,
             "String" => TokenType::TString,
             _ => TokenType::Identifier,
         };
 
         Token {
-            t: t,
-            value: value,
-            loc: (start, end)
+            t,
+            value,
+            span: Span::new(start, self.index)
         }
     }
 
@@ -289,7 +326,7 @@ impl<'a> Lexer<'a> {
         }
 
         match value.as_ref() {
-            "->" => Some(Token { t: TokenType::OpArrow, value, loc: (start, end) }),
+            "->" => Some(Token { t: TokenType::OpArrow, value, span: Span::new(start, end) }),
             _ => {
                 self.back(start);
                 None
@@ -297,6 +334,111 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    fn next_comment(&mut self) {
+        let start = self.index;
+
+        while self.peek().is_some_and(|c| !NEW_LINE.contains(c)) {
+            self.advance();
+        }
+
+        let value = self.orginal[start..self.index].trim_end().to_string();
+        let span = Span::new(start, start + value.len());
+        self.comments.push(Token { t: TokenType::Comment, value, span });
+
+        // the whitespace the comment ends with is trivia of its own
+        self.back(span.end);
+        self.push_trivia(TokenType::Comment, start);
+    }
+
+    // a run of whitespace up to and including the next line break
+    fn next_whitespace(&mut self) {
+        let start = self.index;
+
+        while let Some(c) = self.peek().copied() {
+            if !WHITESPACE.contains(&c) {
+                break;
+            }
+            self.advance();
+            if c == '\n' {
+                break;
+            }
+        }
+
+        self.push_trivia(TokenType::Whitespace, start);
+    }
+
+    fn push_trivia(&mut self, t: TokenType, start: usize) {
+        if let Some(trivia) = self.trivia.as_mut() {
+            let value = self.orginal[start..self.index].to_string();
+            trivia.push(Token { t, value, span: Span::new(start, self.index) });
+        }
+    }
+
+    // integer or decimal, a leading minus is left to the parser
+    fn next_number(&mut self) -> Token {
+        let start = self.index;
+        let mut value = String::new();
+
+        while let Some(c) = self.peek() {
+            let c = *c;
+
+            if c.is_ascii_digit() {
+                value.push(c);
+                self.advance();
+            } else if c == '.' && !value.contains('.') {
+                // only a decimal point when followed by another digit
+                let index = self.index;
+                self.advance();
+                if self.peek().is_some_and(|n| n.is_ascii_digit()) {
+                    value.push(c);
+                } else {
+                    self.back(index);
+                    break;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Token { t: TokenType::Number, value, span: Span::new(start, self.index) }
+    }
+
+    // single line "quoted" string, supports \" and \\ escapes.
+    // unterminated strings come back as an unknown token
+    fn next_string_literal(&mut self) -> Token {
+        let start = self.index;
+        let mut value = String::new();
+
+        self.advance();
+
+        loop {
+            let c = match self.peek() {
+                Some(c) if !NEW_LINE.contains(c) => *c,
+                _ => {
+                    return Token { t: TokenType::Unknown, value: self.orginal[start..self.index].to_string(), span: Span::new(start, self.index) };
+                }
+            };
+
+            self.advance();
+
+            match c {
+                '"' => break,
+                '\\' => {
+                    match self.peek() {
+                        Some('n') => value.push('\n'),
+                        Some('t') => value.push('\t'),
+                        Some(e) if !NEW_LINE.contains(e) => value.push(*e),
+                        _ => continue,
+                    }
+                    self.advance();
+                },
+                _ => value.push(c),
+            }
+        }
+
+        Token { t: TokenType::StringLiteral, value, span: Span::new(start, self.index) }
+    }
+
     fn next_doc_string(&mut self) -> Option<Token> {
         let start = self.index;
         let mut end = start;
@@ -322,8 +464,6 @@ impl<'a> Lexer<'a> {
         // while not new triple quote save chars into string
         let mut doc_string = String::new();
         value = String::new();
-        
-        let start_doc = self.index;
 
         while value != "\"\"\"" {
             let c = if let Some(c) = self.peek() {
@@ -356,32 +496,42 @@ impl<'a> Lexer<'a> {
                         break;
                     } else {
                         value = String::new();
-                        self.back(index + 1); // go back to after the first quote
+                        self.back(index); // go back to the first quote
                     }
                 } else {
                     value = String::new();
-                    self.back(index + 1); // go back to after the first quote
+                    self.back(index); // go back to the first quote
                 }
             }
 
-            end += 1;
             doc_string.push(c);
             self.advance();
         }
 
         let trimmed = doc_string.trim().to_string();
 
-        if trimmed.len() == 0 {
+        if trimmed.is_empty() {
             return None;
         }
 
-        Some(Token { t: TokenType::DocString, value: trimmed, loc: (start_doc, end)})
+        Some(Token { t: TokenType::DocString, value: trimmed, span: Span::new(start, self.index) })
+    }
+}
+
+// whether `name` lexes as a single identifier, keywords and the built-in scalars don't
+pub fn is_identifier(name: &str) -> bool {
+    let tokens = Lexer::new(name).run();
+    let mut tokens = tokens.into_iter();
+
+    match (tokens.next(), tokens.next()) {
+        (Some(t), Some(eof)) => t.t == TokenType::Identifier && t.value == name && eof.t == TokenType::EOF,
+        _ => false,
     }
 }
 
 fn is_valid_identifier(c: Option<&char>) -> bool {
     lazy_static! {
-        static ref VALID_IDENTIFIER_REGEX: regex::Regex = regex::Regex::new(r#"[^\s\n\r0-9\+-/\*\^!#\(\)\{\}=\.,:;|"'\[\]]"#).unwrap();
+        static ref VALID_IDENTIFIER_REGEX: regex::Regex = regex::Regex::new(r#"[^\s\n\r0-9\+-/\*\^!#\(\)\{\}=\.,:;|"'\[\]@]"#).unwrap();
     }
 
     if let Some(c) = c {
@@ -407,7 +557,7 @@ fn is_special_identifier(c: Option<&char>) -> bool {
 
 fn is_quote(c: Option<&char>) -> bool {
     if let Some(c) = c {
-        return c == &'"';
+        c == &'"'
     } else {
         false
     }
@@ -465,6 +615,36 @@ mod tests {
         match_tokens(tokens, expected);
     }
 
+    #[test]
+    fn keeps_comments() {
+        let src = "# first \r\ntype A { # second\n} #";
+        let mut l = Lexer::new(src);
+        let tokens = l.run();
+        let comments: Vec<&str> = tokens.comments().iter().map(|c| c.value.as_str()).collect();
+        let slices: Vec<&str> = tokens.comments().iter().map(|c| &src[c.span.start..c.span.end]).collect();
+
+        assert_eq!(comments, vec!["# first", "# second", "#"]);
+        assert_eq!(slices, comments);
+        assert_eq!(tokens.tokens.len(), 5);
+    }
+
+    #[test]
+    fn lossless_trivia() {
+        let src = "a  # c \r\n\t\"\"\"\"\"\" b";
+        let tokens = Lexer::lossless(src).run();
+        let trivia: Vec<(TokenType, &str)> = tokens.trivia().iter().map(|t| (t.t.clone(), t.value.as_str())).collect();
+
+        assert_eq!(trivia, vec![
+            (TokenType::Whitespace, "  "),
+            (TokenType::Comment, "# c"),
+            (TokenType::Whitespace, " \r\n"),
+            (TokenType::Whitespace, "\t"),
+            (TokenType::DocString, "\"\"\"\"\"\""),
+            (TokenType::Whitespace, " "),
+        ]);
+        assert!(Lexer::new(src).run().trivia().is_empty());
+    }
+
     #[test]
     fn doc_strings() {
         let mut l = Lexer::new(r#"type Movie """\n  \t  it's "" so good to be here\n""" "#);
@@ -494,4 +674,85 @@ mod tests {
 
         match_tokens(tokens, expected);
     }
+
+    #[test]
+    fn spans() {
+        let src = "type Movie { # é\n  \"\"\"doc\"\"\" name: String! -> }";
+        let mut l = Lexer::new(src);
+        let tokens = l.run();
+        let slices: Vec<&str> = tokens.tokens.iter().map(|t| &src[t.span.start..t.span.end]).collect();
+
+        assert_eq!(slices, vec!["type", "Movie", "{", "\"\"\"doc\"\"\"", "name", ":", "String", "!", "->", "}", ""]);
+        assert_eq!(tokens.line_index().line_col(tokens.tokens[4].span.start).to_string(), "2:13");
+    }
+
+    #[test]
+    fn keywords() {
+        let mut l = Lexer::new("type enum Int String enumeration");
+        let tokens = l.run();
+        let expected = vec![
+            TokenType::FieldType,
+            TokenType::Enum,
+            TokenType::TInt,
+            TokenType::TString,
+            TokenType::Identifier,
+            TokenType::EOF,
+        ];
+
+        match_tokens(tokens, expected);
+    }
+
+    #[test]
+    fn identifiers_with_digits() {
+        let src = "field2 v2beta 2fast a[b]";
+        let tokens = Lexer::new(src).run();
+        let values: Vec<(TokenType, &str)> = tokens.tokens.iter().map(|t| (t.t.clone(), &src[t.span.start..t.span.end])).collect();
+
+        assert_eq!(values, vec![
+            (TokenType::Identifier, "field2"),
+            (TokenType::Identifier, "v2beta"),
+            (TokenType::Number, "2"),
+            (TokenType::Identifier, "fast"),
+            (TokenType::Identifier, "a"),
+            (TokenType::BracketL, "["),
+            (TokenType::Identifier, "b"),
+            (TokenType::BracketR, "]"),
+            (TokenType::EOF, ""),
+        ]);
+        assert!(is_identifier("email2"));
+    }
+
+    #[test]
+    fn annotation_tokens() {
+        let src = r#"@default(value: -1.5, names: ["a \"b\"", ""]) 42."#;
+        let mut l = Lexer::new(src);
+        let tokens = l.run();
+        let expected = vec![
+            TokenType::At,
+            TokenType::Identifier,
+            TokenType::ParenL,
+            TokenType::Identifier,
+            TokenType::Colon,
+            TokenType::OpMinus,
+            TokenType::Number,
+            TokenType::Comma,
+            TokenType::Identifier,
+            TokenType::Colon,
+            TokenType::BracketL,
+            TokenType::StringLiteral,
+            TokenType::Comma,
+            TokenType::StringLiteral,
+            TokenType::BracketR,
+            TokenType::ParenR,
+            TokenType::Number,
+            TokenType::Unknown,
+            TokenType::EOF,
+        ];
+
+        assert_eq!(tokens.tokens[6].value, "1.5");
+        assert_eq!(tokens.tokens[11].value, "a \"b\"");
+        assert_eq!(tokens.tokens[13].value, "");
+        assert_eq!(tokens.tokens[16].value, "42");
+        match_tokens(tokens, expected);
+    }
 }
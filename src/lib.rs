@@ -4,6 +4,21 @@ pub mod _helpers;
 pub mod lexer;
 pub mod parser;
 pub mod ast;
+pub mod span;
+pub mod diagnostics;
+pub mod migration;
+pub mod diff;
+pub mod compat;
+pub mod codegen;
+pub mod import;
+pub mod format;
+pub mod cst;
+pub mod refactor;
+pub mod lint;
+pub mod config;
+pub mod cli;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 
 
 
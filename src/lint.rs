@@ -0,0 +1,71 @@
+use crate::{
+    ast::Root,
+    codegen::{self, primary_key_field},
+    diagnostics::Diagnostic,
+    span::Span,
+};
+
+// checks on top of what the parser enforces, enabled by name in the project config.
+// everything they find is a warning
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rule {
+    MissingDoc, // models and enums without a doc string
+    TypeNameCase, // model and enum names in PascalCase
+    FieldNameCase, // field names starting with a lowercase letter
+    MissingPrimaryKey, // models without `@primary` or an `id` field
+}
+
+impl Rule {
+    pub const ALL: [Rule; 4] = [Rule::MissingDoc, Rule::TypeNameCase, Rule::FieldNameCase, Rule::MissingPrimaryKey];
+
+    pub fn from_name(name: &str) -> Option<Rule> {
+        Rule::ALL.iter().copied().find(|r| r.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::MissingDoc => "missing-doc",
+            Rule::TypeNameCase => "type-name-case",
+            Rule::FieldNameCase => "field-name-case",
+            Rule::MissingPrimaryKey => "missing-primary-key",
+        }
+    }
+}
+
+// diagnostics are in source order and use the rule name as their code
+pub fn lint(root: &Root, rules: &[Rule]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let warning = |rule: Rule, span: Span, message: String| Diagnostic::warning(rule.name(), message).with_span(span);
+
+    let types = root.types.iter().map(|m| ("model", &m.name, &m.doc, m.name_span))
+        .chain(root.enums.iter().map(|e| ("enum", &e.name, &e.doc, e.name_span)));
+
+    for (kind, name, doc, span) in types {
+        if rules.contains(&Rule::MissingDoc) && doc.is_none() {
+            diagnostics.push(warning(Rule::MissingDoc, span, format!("{} `{}` has no doc string", kind, name))
+                .with_help("describe it with `\"\"\" ... \"\"\"` above the declaration"));
+        }
+
+        if rules.contains(&Rule::TypeNameCase) && codegen::pascal_case(name) != *name {
+            diagnostics.push(warning(Rule::TypeNameCase, span, format!("{} `{}` should be in PascalCase", kind, name))
+                .with_help(format!("rename it to `{}`", codegen::pascal_case(name))));
+        }
+    }
+
+    for model in root.types.iter() {
+        if rules.contains(&Rule::MissingPrimaryKey) && primary_key_field(root, model).is_none() {
+            diagnostics.push(warning(Rule::MissingPrimaryKey, model.name_span, format!("model `{}` has no primary key", model.name))
+                .with_help("mark a field with `@primary` or add an `id` field"));
+        }
+
+        if rules.contains(&Rule::FieldNameCase) {
+            for field in model.fields.iter().filter(|f| f.name.starts_with(|c: char| !c.is_lowercase())) {
+                diagnostics.push(warning(Rule::FieldNameCase, field.name_span, format!("field `{}` on `{}` should start with a lowercase letter", field.name, model.name))
+                    .with_help(format!("rename it to `{}`", codegen::camel_case(&field.name))));
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|d| d.span.map(|s| s.start));
+    diagnostics
+}
@@ -0,0 +1,13 @@
+use lsp_server::Connection;
+
+// `odetto-lsp` speaks the language server protocol over stdin and stdout
+fn main() -> odetto::lsp::LspResult<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    odetto::lsp::run(&connection)?;
+
+    drop(connection);
+    io_threads.join()?;
+
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use crate::{
+    ast::{Root, ModelTypeDef, EnumDef, FieldDef, FieldType, annotations_suffix},
+    lexer::{Lexer, Tokens, TokenType},
+    parser::{Parser, ParseOptions},
+    diagnostics::Diagnostic,
+    span::Span,
+};
+
+// an open `.odet` file, parsed with recovery so a half written schema still answers requests
+pub struct Document {
+    pub text: String,
+    pub tokens: Tokens,
+    pub root: Root,
+    pub diagnostics: Vec<Diagnostic>,
+    pub scalars: Vec<String>, // the project's custom scalars the document was parsed with
+}
+
+// what a name in the source refers to
+#[derive(Clone, Copy)]
+pub enum Symbol<'a> {
+    Model(&'a ModelTypeDef),
+    Enum(&'a EnumDef),
+    Field(&'a ModelTypeDef, &'a FieldDef),
+}
+
+impl Symbol<'_> {
+    pub fn name_span(&self) -> Span {
+        match self {
+            Symbol::Model(m) => m.name_span,
+            Symbol::Enum(e) => e.name_span,
+            Symbol::Field(_, f) => f.name_span,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompletionKind {
+    Scalar,
+    Model,
+    Enum,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub doc: Option<String>,
+}
+
+impl Document {
+    pub fn new(text: String, options: ParseOptions) -> Document {
+        let tokens = Lexer::new(&text).run();
+        let scalars = options.scalars.clone();
+        let (root, diagnostics) = Parser::with_options(&tokens, options).parse_recovering();
+
+        Document { text, tokens, root, diagnostics, scalars }
+    }
+
+    // the symbol named at `offset` and the span of that name, a cursor right after a name still counts
+    pub fn symbol_at(&self, offset: usize) -> Option<(Symbol<'_>, Span)> {
+        let touches = |span: Span| span.start <= offset && offset <= span.end;
+
+        for model in self.root.types.iter() {
+            if touches(model.name_span) {
+                return Some((Symbol::Model(model), model.name_span));
+            }
+
+            for field in model.fields.iter() {
+                if touches(field.name_span) {
+                    return Some((Symbol::Field(model, field), field.name_span));
+                }
+
+                if let FieldType::Identfier(name, span) = &field.field_type {
+                    if touches(*span) {
+                        return self.definition_of(name).map(|s| (s, *span));
+                    }
+                }
+
+                if let Some(relation) = field.relation.as_ref().filter(|r| touches(r.inverse_span)) {
+                    let target = self.root.model(field.field_type.name())?;
+                    let inverse = self.root.relation_inverse(field)?;
+                    return Some((Symbol::Field(target, inverse), relation.inverse_span));
+                }
+            }
+        }
+
+        self.root.enums.iter()
+            .find(|e| touches(e.name_span))
+            .map(|e| (Symbol::Enum(e), e.name_span))
+    }
+
+    fn definition_of(&self, name: &str) -> Option<Symbol<'_>> {
+        self.root.model(name).map(Symbol::Model).or_else(|| self.root.enum_def(name).map(Symbol::Enum))
+    }
+
+    pub fn definition(&self, offset: usize) -> Option<Span> {
+        self.symbol_at(offset).map(|(symbol, _)| symbol.name_span())
+    }
+
+    // every place the symbol at `offset` is named, in source order
+    pub fn references(&self, offset: usize, include_declaration: bool) -> Vec<Span> {
+        let symbol = if let Some((s, _)) = self.symbol_at(offset) { s } else { return Vec::new() };
+        let mut spans = Vec::new();
+
+        if include_declaration {
+            spans.push(symbol.name_span());
+        }
+
+        for model in self.root.types.iter() {
+            for field in model.fields.iter() {
+                match symbol {
+                    Symbol::Model(ModelTypeDef { name, .. }) | Symbol::Enum(EnumDef { name, .. }) => {
+                        if let FieldType::Identfier(n, span) = &field.field_type {
+                            if n == name {
+                                spans.push(*span);
+                            }
+                        }
+                    },
+                    Symbol::Field(target, inverse) => {
+                        if let Some(relation) = &field.relation {
+                            if field.field_type.name() == target.name && relation.inverse == inverse.name {
+                                spans.push(relation.inverse_span);
+                            }
+                        }
+                    },
+                }
+            }
+        }
+
+        spans.sort_by_key(|s| s.start);
+        spans
+    }
+
+    // markdown with the declaration and its doc string
+    pub fn hover(&self, offset: usize) -> Option<(String, Span)> {
+        let (symbol, span) = self.symbol_at(offset)?;
+
+        let (declaration, doc) = match symbol {
+            Symbol::Model(m) => (format!("type {}{}", m.name, annotations_suffix(&m.annotations)), &m.doc),
+            Symbol::Enum(e) => {
+                let variants: Vec<&str> = e.variants.iter().map(|v| v.name.as_str()).collect();
+                (format!("enum {}{} {{ {} }}", e.name, annotations_suffix(&e.annotations), variants.join(" ")), &e.doc)
+            },
+            Symbol::Field(m, f) => (format!("{}.{}", m.name, f), &f.doc),
+        };
+
+        let mut contents = format!("```odetto\n{}\n```", declaration);
+        if let Some(doc) = doc {
+            contents.push_str(&format!("\n\n{}", doc));
+        }
+
+        Some((contents, span))
+    }
+
+    // field types when the cursor is after a field's `:` or the `[` of a list type
+    pub fn completions(&self, offset: usize) -> Vec<Completion> {
+        let before: Vec<&TokenType> = self.tokens.into_iter()
+            .filter(|t| t.t != TokenType::EOF && t.span.end <= offset)
+            // the name being typed
+            .filter(|t| !(t.span.end == offset && is_type_name(&t.t)))
+            .map(|t| &t.t)
+            .collect();
+
+        let rest = match before.as_slice() {
+            [rest @ .., TokenType::Identifier, TokenType::Colon] => rest,
+            [rest @ .., TokenType::Identifier, TokenType::Colon, TokenType::BracketL] => rest,
+            _ => return Vec::new(),
+        };
+        // `@name(arg: ` is an annotation argument, not a field
+        if matches!(rest.last(), Some(TokenType::ParenL) | Some(TokenType::Comma)) {
+            return Vec::new();
+        }
+
+        let mut completions: Vec<Completion> = ["Int", "String"].iter().copied()
+            .chain(self.scalars.iter().map(String::as_str))
+            .map(|s| Completion { label: s.to_string(), kind: CompletionKind::Scalar, doc: None })
+            .collect();
+
+        // names come from the tokens so definitions the parser gave up on are still offered
+        let tokens: Vec<_> = self.tokens.into_iter().collect();
+        for pair in tokens.windows(2) {
+            let kind = match pair[0].t {
+                TokenType::FieldType => CompletionKind::Model,
+                TokenType::Enum => CompletionKind::Enum,
+                _ => continue,
+            };
+            if pair[1].t != TokenType::Identifier || completions.iter().any(|c| c.label == pair[1].value) {
+                continue;
+            }
+
+            let doc = match kind {
+                CompletionKind::Model => self.root.model(&pair[1].value).and_then(|m| m.doc.clone()),
+                _ => self.root.enum_def(&pair[1].value).and_then(|e| e.doc.clone()),
+            };
+            completions.push(Completion { label: pair[1].value.clone(), kind, doc });
+        }
+
+        completions
+    }
+}
+
+fn is_type_name(t: &TokenType) -> bool {
+    *t == TokenType::Identifier || *t == TokenType::TInt || *t == TokenType::TString
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response, ErrorCode};
+use lsp_types::{
+    notification::{self, Notification as _},
+    request::{self, Request as _},
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionResponse, DiagnosticSeverity, DocumentSymbol,
+    DocumentSymbolResponse, GotoDefinitionResponse, Hover, HoverContents, HoverProviderCapability, Location, MarkupContent,
+    MarkupKind, NumberOrString, OneOf, Position, PublishDiagnosticsParams, Range, ServerCapabilities, SymbolKind,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+};
+
+use crate::{
+    diagnostics::{self, Diagnostic},
+    parser::ParseOptions,
+    span::{Span, LineIndex, LineCol},
+};
+#[cfg(feature = "config")]
+use crate::config;
+
+mod document;
+
+pub use document::{Document, Symbol, Completion, CompletionKind};
+
+pub type LspResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// open documents by uri, `Uri` itself can't be a key
+type Documents = HashMap<String, Document>;
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![String::from(":"), String::from("[")]),
+            ..Default::default()
+        }),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+// answers requests until the client shuts the server down, the documents are kept in memory
+// and fully resent by the client on every change
+pub fn run(connection: &Connection) -> LspResult<()> {
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+
+    let mut documents = Documents::new();
+
+    for message in connection.receiver.iter() {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                connection.sender.send(Message::Response(respond(&documents, request)))?;
+            },
+            Message::Notification(notification) => {
+                if let Some(uri) = update(&mut documents, notification)? {
+                    let diagnostics = documents.get(uri.as_str()).map_or_else(Vec::new, |d| {
+                        let index = d.tokens.line_index();
+                        d.diagnostics.iter().map(|diagnostic| lsp_diagnostic(diagnostic, index)).collect()
+                    });
+
+                    let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+                    connection.sender.send(Message::Notification(Notification::new(
+                        notification::PublishDiagnostics::METHOD.to_string(),
+                        params,
+                    )))?;
+                }
+            },
+            Message::Response(_) => {},
+        }
+    }
+
+    Ok(())
+}
+
+// keeps the documents in sync, returns the document whose diagnostics have to be published
+fn update(documents: &mut Documents, notification: Notification) -> LspResult<Option<Uri>> {
+    match notification.method.as_str() {
+        notification::DidOpenTextDocument::METHOD => {
+            let params: lsp_types::DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+            let uri = params.text_document.uri;
+            documents.insert(uri.to_string(), Document::new(params.text_document.text, parse_options(&uri)));
+            Ok(Some(uri))
+        },
+        notification::DidChangeTextDocument::METHOD => {
+            let params: lsp_types::DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+            let uri = params.text_document.uri;
+            match params.content_changes.into_iter().last() {
+                Some(change) => {
+                    documents.insert(uri.to_string(), Document::new(change.text, parse_options(&uri)));
+                    Ok(Some(uri))
+                },
+                None => Ok(None),
+            }
+        },
+        notification::DidCloseTextDocument::METHOD => {
+            let params: lsp_types::DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+            let uri = params.text_document.uri;
+            documents.remove(uri.as_str());
+            // clears the diagnostics of the closed file
+            Ok(Some(uri))
+        },
+        _ => Ok(None),
+    }
+}
+
+// the options of the project config nearest to the file, so custom scalars resolve like in `odetto-cli check`
+#[cfg(feature = "config")]
+fn parse_options(uri: &Uri) -> ParseOptions {
+    if uri.scheme().map(|s| s.as_str()) != Some("file") {
+        return ParseOptions::default();
+    }
+
+    let path = uri.path().as_estr().decode().into_string_lossy();
+    let config = match std::path::Path::new(path.as_ref()).parent().and_then(config::find) {
+        Some(p) => p,
+        None => return ParseOptions::default(),
+    };
+
+    // stdout is the connection, a broken config is reported on stderr and the document parsed without it
+    match config::load(&config) {
+        Ok(c) => c.parse_options(),
+        Err(e) => {
+            eprintln!("{}", e);
+            ParseOptions::default()
+        },
+    }
+}
+
+#[cfg(not(feature = "config"))]
+fn parse_options(_uri: &Uri) -> ParseOptions {
+    ParseOptions::default()
+}
+
+fn respond(documents: &Documents, request: Request) -> Response {
+    let id = request.id.clone();
+
+    let result = match request.method.as_str() {
+        request::GotoDefinition::METHOD => extract::<request::GotoDefinition>(request).and_then(|params| {
+            let position = params.text_document_position_params;
+            let uri = position.text_document.uri;
+            let result = with_offset(documents, &uri, position.position, |document, offset| {
+                let span = document.definition(offset)?;
+                Some(GotoDefinitionResponse::Scalar(Location::new(uri.clone(), range(document.tokens.line_index(), span))))
+            });
+            Ok(serde_json::to_value(result)?)
+        }),
+        request::References::METHOD => extract::<request::References>(request).and_then(|params| {
+            let position = params.text_document_position;
+            let include_declaration = params.context.include_declaration;
+            let uri = position.text_document.uri;
+            let result = with_offset(documents, &uri, position.position, |document, offset| {
+                let locations: Vec<Location> = document.references(offset, include_declaration).into_iter()
+                    .map(|span| Location::new(uri.clone(), range(document.tokens.line_index(), span)))
+                    .collect();
+                Some(locations)
+            });
+            Ok(serde_json::to_value(result)?)
+        }),
+        request::HoverRequest::METHOD => extract::<request::HoverRequest>(request).and_then(|params| {
+            let position = params.text_document_position_params;
+            let result = with_offset(documents, &position.text_document.uri, position.position, |document, offset| {
+                let (value, span) = document.hover(offset)?;
+                Some(Hover {
+                    contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+                    range: Some(range(document.tokens.line_index(), span)),
+                })
+            });
+            Ok(serde_json::to_value(result)?)
+        }),
+        request::Completion::METHOD => extract::<request::Completion>(request).and_then(|params| {
+            let position = params.text_document_position;
+            let result = with_offset(documents, &position.text_document.uri, position.position, |document, offset| {
+                let items: Vec<CompletionItem> = document.completions(offset).into_iter().map(completion_item).collect();
+                Some(CompletionResponse::Array(items))
+            });
+            Ok(serde_json::to_value(result)?)
+        }),
+        request::DocumentSymbolRequest::METHOD => extract::<request::DocumentSymbolRequest>(request).and_then(|params| {
+            let result = documents.get(params.text_document.uri.as_str()).map(|d| DocumentSymbolResponse::Nested(symbols(d)));
+            Ok(serde_json::to_value(result)?)
+        }),
+        _ => return Response::new_err(id, ErrorCode::MethodNotFound as i32, format!("unknown method {}", request.method)),
+    };
+
+    match result {
+        Ok(value) => Response { id, result: Some(value), error: None },
+        Err(e) => Response::new_err(id, ErrorCode::InvalidParams as i32, e.to_string()),
+    }
+}
+
+fn extract<R: request::Request>(request: Request) -> LspResult<R::Params> {
+    let (_, params): (RequestId, R::Params) = request.extract(R::METHOD)?;
+    Ok(params)
+}
+
+fn with_offset<T>(documents: &Documents, uri: &Uri, position: Position, f: impl FnOnce(&Document, usize) -> Option<T>) -> Option<T> {
+    let document = documents.get(uri.as_str())?;
+    let offset = offset(document.tokens.line_index(), position)?;
+    f(document, offset)
+}
+
+fn completion_item(completion: Completion) -> CompletionItem {
+    let kind = match completion.kind {
+        CompletionKind::Scalar => CompletionItemKind::KEYWORD,
+        CompletionKind::Model => CompletionItemKind::STRUCT,
+        CompletionKind::Enum => CompletionItemKind::ENUM,
+    };
+
+    CompletionItem {
+        label: completion.label,
+        kind: Some(kind),
+        documentation: completion.doc.map(lsp_types::Documentation::String),
+        ..Default::default()
+    }
+}
+
+// models with their fields and enums with their variants
+#[allow(deprecated)] // `DocumentSymbol::deprecated` has to be set
+fn symbols(document: &Document) -> Vec<DocumentSymbol> {
+    let index = document.tokens.line_index();
+    let symbol = |name: &str, detail: Option<String>, kind, span, name_span, children| DocumentSymbol {
+        name: name.to_string(),
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: range(index, span),
+        selection_range: range(index, name_span),
+        children,
+    };
+
+    let mut symbols: Vec<(usize, DocumentSymbol)> = Vec::new();
+
+    for model in document.root.types.iter() {
+        let fields = model.fields.iter()
+            .map(|f| symbol(&f.name, Some(f.type_string()), SymbolKind::FIELD, f.span, f.name_span, None))
+            .collect();
+        symbols.push((model.span.start, symbol(&model.name, None, SymbolKind::STRUCT, model.span, model.name_span, Some(fields))));
+    }
+    for enum_def in document.root.enums.iter() {
+        let variants = enum_def.variants.iter()
+            .map(|v| symbol(&v.name, None, SymbolKind::ENUM_MEMBER, v.span, v.span, None))
+            .collect();
+        symbols.push((enum_def.span.start, symbol(&enum_def.name, None, SymbolKind::ENUM, enum_def.span, enum_def.name_span, Some(variants))));
+    }
+
+    symbols.sort_by_key(|(start, _)| *start);
+    symbols.into_iter().map(|(_, s)| s).collect()
+}
+
+fn lsp_diagnostic(diagnostic: &Diagnostic, index: &LineIndex) -> lsp_types::Diagnostic {
+    let severity = match diagnostic.severity {
+        diagnostics::Severity::Error => DiagnosticSeverity::ERROR,
+        diagnostics::Severity::Warning => DiagnosticSeverity::WARNING,
+    };
+
+    let mut message = diagnostic.message.clone();
+    if let Some(help) = &diagnostic.help {
+        message.push_str(&format!("\nhelp: {}", help));
+    }
+    for note in diagnostic.notes.iter() {
+        message.push_str(&format!("\nnote: {}", note));
+    }
+
+    lsp_types::Diagnostic {
+        range: range(index, diagnostic.span.unwrap_or_default()),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(diagnostic.code.clone())),
+        source: Some(String::from("odetto")),
+        message,
+        ..Default::default()
+    }
+}
+
+// lsp positions are 0 based and count utf-16 code units
+pub fn position(index: &LineIndex, offset: usize) -> Position {
+    let line_col = index.line_col(offset);
+    let line = index.line(line_col.line).unwrap_or("");
+    let character: usize = line.chars().take(line_col.col - 1).map(char::len_utf16).sum();
+
+    Position::new(line_col.line as u32 - 1, character as u32)
+}
+
+pub fn offset(index: &LineIndex, position: Position) -> Option<usize> {
+    let line = index.line(position.line as usize + 1)?;
+    let mut units = 0;
+    let mut col = 1;
+
+    for c in line.chars() {
+        if units >= position.character as usize {
+            break;
+        }
+        units += c.len_utf16();
+        col += 1;
+    }
+
+    index.offset(LineCol { line: position.line as usize + 1, col })
+}
+
+pub fn range(index: &LineIndex, span: Span) -> Range {
+    Range::new(position(index, span.start), position(index, span.end))
+}
@@ -0,0 +1,201 @@
+use crate::{
+    _helpers::{MigrationError, MigrationResult, RelationError},
+    span::Span,
+    ast::{Root, FieldDef, FieldType, Cardinality, Migration, Transition, ModelTransition, FieldTransition},
+};
+
+// applies the transitions in order on top of `root` and returns the new schema.
+// references and relations are checked against the result
+pub fn apply(root: &Root, migration: &Migration) -> MigrationResult<Root> {
+    let mut root = root.clone();
+
+    for transition in migration.transitions.iter() {
+        match transition {
+            Transition::AddModel(model) => {
+                if root.is_declared(&model.name) {
+                    return Err(MigrationError::DuplicateModelError(model.name.clone(), model.name_span));
+                }
+
+                root.types.push(model.clone());
+            },
+            Transition::RemoveModel { name, span } => {
+                let index = model_index(&root, name, *span)?;
+                root.types.remove(index);
+            },
+            Transition::ChangeModel(change) => change_model(&mut root, change)?,
+            Transition::AddEnum(enum_def) => {
+                if root.is_declared(&enum_def.name) {
+                    return Err(MigrationError::DuplicateEnumError(enum_def.name.clone(), enum_def.name_span));
+                }
+
+                root.enums.push(enum_def.clone());
+            },
+            Transition::RemoveEnum { name, span } => {
+                let index = enum_index(&root, name, *span)?;
+                root.enums.remove(index);
+            },
+            Transition::ReplaceEnum(enum_def) => {
+                let index = enum_index(&root, &enum_def.name, enum_def.name_span)?;
+                root.enums[index] = enum_def.clone();
+            },
+        }
+    }
+
+    check_references(&root)?;
+    resolve_relations(&mut root)?;
+
+    Ok(root)
+}
+
+fn change_model(root: &mut Root, change: &ModelTransition) -> MigrationResult<()> {
+    if let Some(from) = &change.rename_from {
+        let index = model_index(root, from, change.span)?;
+
+        if *from != change.name && root.is_declared(&change.name) {
+            return Err(MigrationError::DuplicateModelError(change.name.clone(), change.span));
+        }
+
+        root.types[index].name = change.name.clone();
+        rename_model_references(root, from, &change.name);
+    }
+
+    let index = model_index(root, &change.name, change.span)?;
+
+    if change.clear_doc {
+        root.types[index].doc = None;
+    }
+    if change.clear_annotations {
+        root.types[index].annotations = Vec::new();
+    }
+
+    if change.doc.is_some() {
+        root.types[index].doc = change.doc.clone();
+    }
+
+    if let Some(annotations) = &change.annotations {
+        root.types[index].annotations = annotations.clone();
+    }
+
+    for transition in change.fields.iter() {
+        let fields = &mut root.types[index].fields;
+
+        match transition {
+            FieldTransition::Add(field) => {
+                if fields.iter().any(|f| f.name == field.name) {
+                    return Err(MigrationError::DuplicateFieldError(change.name.clone(), field.name.clone(), field.name_span));
+                }
+
+                fields.push(field.clone());
+            },
+            FieldTransition::Remove { name, span } => {
+                let position = field_index(fields, &change.name, name, *span)?;
+                fields.remove(position);
+            },
+            FieldTransition::Change { field, rename_from } => {
+                let old_name = rename_from.as_ref().unwrap_or(&field.name);
+                let position = field_index(fields, &change.name, old_name, field.name_span)?;
+
+                if *old_name != field.name && fields.iter().any(|f| f.name == field.name) {
+                    return Err(MigrationError::DuplicateFieldError(change.name.clone(), field.name.clone(), field.name_span));
+                }
+
+                fields[position] = field.clone();
+
+                if *old_name != field.name {
+                    rename_inverse_references(root, &change.name, old_name, &field.name);
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn model_index(root: &Root, name: &str, span: Span) -> MigrationResult<usize> {
+    root.types.iter()
+        .position(|t| t.name == name)
+        .ok_or_else(|| MigrationError::UnknownModelError(name.to_string(), span))
+}
+
+fn enum_index(root: &Root, name: &str, span: Span) -> MigrationResult<usize> {
+    root.enums.iter()
+        .position(|e| e.name == name)
+        .ok_or_else(|| MigrationError::UnknownEnumError(name.to_string(), span))
+}
+
+fn field_index(fields: &[FieldDef], model: &str, name: &str, span: Span) -> MigrationResult<usize> {
+    fields.iter()
+        .position(|f| f.name == name)
+        .ok_or_else(|| MigrationError::UnknownFieldError(model.to_string(), name.to_string(), span))
+}
+
+fn rename_model_references(root: &mut Root, from: &str, to: &str) {
+    for model in root.types.iter_mut() {
+        for field in model.fields.iter_mut() {
+            if let FieldType::Identfier(name, _) = &mut field.field_type {
+                if name == from {
+                    *name = to.to_string();
+                }
+            }
+        }
+    }
+}
+
+// relations on `model` pointing at a renamed field follow the rename
+fn rename_inverse_references(root: &mut Root, model: &str, from: &str, to: &str) {
+    for other in root.types.iter_mut() {
+        for field in other.fields.iter_mut() {
+            if field.field_type.name() != model {
+                continue;
+            }
+
+            if let Some(relation) = field.relation.as_mut() {
+                if relation.inverse == from {
+                    relation.inverse = to.to_string();
+                }
+            }
+        }
+    }
+}
+
+fn check_references(root: &Root) -> MigrationResult<()> {
+    for model in root.types.iter() {
+        for field in model.fields.iter() {
+            if let FieldType::Identfier(name, _) = &field.field_type {
+                if !root.is_declared(name) {
+                    return Err(MigrationError::MissingReferenceError(model.name.clone(), field.name.clone(), name.clone()));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn resolve_relations(root: &mut Root) -> MigrationResult<()> {
+    let mut cardinalities = Vec::new();
+
+    for (m, model) in root.types.iter().enumerate() {
+        for (f, field) in model.fields.iter().enumerate() {
+            let inverse = match root.check_relation(model, field) {
+                Ok(Some(i)) => i,
+                Ok(None) => continue,
+                Err(RelationError::MissingInverseError) => {
+                    let inverse = field.relation.as_ref().map(|r| r.inverse.clone()).unwrap_or_default();
+                    return Err(MigrationError::MissingInverseFieldError(model.name.clone(), field.name.clone(), inverse));
+                },
+                Err(e) => return Err(MigrationError::InvalidRelationError(model.name.clone(), field.name.clone(), e)),
+            };
+
+            cardinalities.push((m, f, Cardinality::between(field, inverse)));
+        }
+    }
+
+    for (m, f, cardinality) in cardinalities {
+        if let Some(r) = root.types[m].fields[f].relation.as_mut() {
+            r.cardinality = Some(cardinality);
+        }
+    }
+
+    Ok(())
+}
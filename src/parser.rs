@@ -1,36 +1,142 @@
 use std::iter::Peekable;
 
 use crate::{
-    _helpers::{ParseError, ParseResult, TokenInfo},
+    _helpers::{ParseError, ParseResult, TokenInfo, RelationError},
     lexer::{Token, Tokens, TokenIter, TokenType},
-    ast::{Root, ModelTypeDef, FieldDef, FieldType, FieldTypeType},
+    ast::{self, Root, ModelTypeDef, EnumDef, EnumVariant, FieldDef, FieldType, FieldTypeType, Annotation, AnnotationArg, AnnotationValue, Relation, Cardinality,
+        Migration, Transition, ModelTransition, FieldTransition},
+    span::{Span, LineIndex},
+    diagnostics::Diagnostic,
 };
 
+// settings that come from the project, see `config.rs`
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    pub scalars: Vec<String>, // custom scalars such as `DateTime`, used like `Int` and `String`
+}
+
+// models, enums and custom scalars share one namespace.
+// declarations and renames both go through this so they can't disagree on what collides
+pub(crate) fn type_name_taken<'n>(declared: impl IntoIterator<Item = &'n String>, name: &str, options: &ParseOptions) -> bool {
+    options.scalars.iter().any(|s| s == name) || declared.into_iter().any(|d| d == name)
+}
+
+// the field of the model already called `name`
+pub(crate) fn field_name_taken<'f>(fields: &'f [FieldDef], name: &str) -> Option<&'f FieldDef> {
+    ast::field(fields, name)
+}
+
+enum Definition {
+    Model(ModelTypeDef),
+    Enum(EnumDef),
+}
+
 pub struct Parser<'a> {
     tokens: Peekable<TokenIter<'a>>,
+    line_index: &'a LineIndex,
+    prev_span: Span,
     model_identifiers: Vec<String>,
-    referenced_identifiers: Vec<(TokenInfo, String)>
+    referenced_identifiers: Vec<(TokenInfo, String)>,
+    recover: bool,
+    errors: Vec<ParseError>,
+    options: ParseOptions,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(tokens: &'a Tokens) -> Parser<'a> {
+        Parser::with_options(tokens, ParseOptions::default())
+    }
+
+    pub fn with_options(tokens: &'a Tokens, options: ParseOptions) -> Parser<'a> {
         Parser {
             tokens: tokens.into_iter().peekable(),
+            line_index: tokens.line_index(),
+            prev_span: Span::default(),
             model_identifiers: Vec::new(),
-            referenced_identifiers: Vec::new()
+            referenced_identifiers: Vec::new(),
+            recover: false,
+            errors: Vec::new(),
+            options,
         }
     }
 
     pub fn parse(&mut self) -> ParseResult<Root> {
-        // @todo load config from yml
+        let mut root = self.parse_models()?;
+
+        if let Some(e) = self.check_identifiers().into_iter().next() {
+            return Err(e);
+        }
+
+        if let Some(e) = self.check_relations(&mut root).into_iter().next() {
+            return Err(e);
+        }
+
+        Ok(root)
+    }
+
+    // keeps going after errors, resynchronizing at the next `type` keyword or `}`,
+    // and returns whatever could be parsed alongside every problem found
+    pub fn parse_recovering(&mut self) -> (Root, Vec<Diagnostic>) {
+        self.recover = true;
+
+        let mut root = self.parse_models().unwrap_or_default();
+        let mut errors = std::mem::take(&mut self.errors);
+        errors.extend(self.check_identifiers());
+        errors.extend(self.check_relations(&mut root));
+
+        let mut diagnostics: Vec<Diagnostic> = errors.iter()
+            .map(|e| Diagnostic::from_parse_error(e, self.line_index))
+            .collect();
+        diagnostics.sort_by_key(|d| d.span.map(|s| s.start));
+
+        (root, diagnostics)
+    }
+
+    // every field type that names a model or an enum, in source order.
+    // filled in while parsing, e.g. to find everything a rename has to touch
+    pub fn referenced_identifiers(&self) -> &[(TokenInfo, String)] {
+        &self.referenced_identifiers
+    }
+
+    // migration files mark added (`+`) and removed (`-`) models and fields,
+    // anything unmarked inside a model replaces the existing definition.
+    // references are only checked once the migration is applied to a schema
+    pub fn parse_migration(&mut self) -> ParseResult<Migration> {
+        let mut migration = Migration::new();
+
+        while let Some(t) = self.tokens.peek() {
+            if t.t == TokenType::EOF {
+                break;
+            }
+
+            let transition = self.next_transition()?;
+            migration.transitions.push(transition);
+        }
+
+        Ok(migration)
+    }
+
+    fn parse_models(&mut self) -> ParseResult<Root> {
         let mut root = Root::new();
 
         loop {
-            let next = self.next_model()?;
-
-            match next {
-                Some(n) => root.types.push(n),
-                None => break,
+            // duplicates are only returned while recovering and have already been reported
+            match self.next_definition() {
+                Ok(Some(Definition::Model(n))) => {
+                    if !type_name_taken(root.type_names(), &n.name, &self.options) {
+                        root.types.push(n);
+                    }
+                },
+                Ok(Some(Definition::Enum(n))) => {
+                    if !type_name_taken(root.type_names(), &n.name, &self.options) {
+                        root.enums.push(n);
+                    }
+                },
+                Ok(None) => break,
+                Err(e) => {
+                    self.report(e)?;
+                    self.synchronize();
+                }
             }
 
             if let Some(n) = self.tokens.peek() {
@@ -40,13 +146,11 @@ impl<'a> Parser<'a> {
             }
         }
 
-        self.check_identifiers()?;
-
         Ok(root)
     }
 
-    fn next_model(&mut self) -> ParseResult<Option<ModelTypeDef>> {
-        let mut annotation = None;
+    fn next_definition(&mut self) -> ParseResult<Option<Definition>> {
+        let mut doc = None;
         let mut token = if let Some(t) = self.tokens.peek() {
             *t
         } else {
@@ -54,152 +158,613 @@ impl<'a> Parser<'a> {
         };
 
         if token.t == TokenType::DocString {
-            annotation = Some(token.value.clone());
+            doc = Some(token.value.clone());
 
-            self.tokens.next();
+            self.bump();
 
             token = if let Some(t) = self.tokens.peek() {
                 *t
             } else {
                 // no type for field
-                return Err(ParseError::MissingFieldTypeError(TokenInfo { loc: token.loc }));
+                return Err(ParseError::MissingFieldTypeError(self.info(token)));
             };
         }
 
-        if token.t != TokenType::FieldType {
-            return Err(ParseError::GenericError(TokenInfo { loc: token.loc }))
+        match token.t {
+            TokenType::FieldType => Ok(Some(Definition::Model(self.next_model(doc)?))),
+            TokenType::Enum => Ok(Some(Definition::Enum(self.next_enum(doc)?))),
+            _ => Err(ParseError::GenericError(self.info(token))),
         }
+    }
+
+    fn next_model(&mut self, doc: Option<String>) -> ParseResult<ModelTypeDef> {
+        let (start, name, name_span, annotations) = self.next_header()?;
+        let fields = self.get_fields()?;
+
+        Ok(ModelTypeDef { name, fields, doc, annotations, span: start.to(self.prev_span), name_span })
+    }
+
+    fn next_enum(&mut self, doc: Option<String>) -> ParseResult<EnumDef> {
+        let (start, name, name_span, annotations) = self.next_header()?;
+        let variants = self.get_variants()?;
+
+        Ok(EnumDef { name, variants, doc, annotations, span: start.to(self.prev_span), name_span })
+    }
+
+    fn next_transition(&mut self) -> ParseResult<Transition> {
+        let mut doc = None;
+        let mut token = if let Some(t) = self.tokens.peek() {
+            *t
+        } else {
+            return Err(ParseError::UnknownError);
+        };
 
-        self.tokens.next();
+        if token.t == TokenType::DocString {
+            doc = Some(token.value.clone());
+
+            self.bump();
+
+            token = if let Some(t) = self.tokens.peek() {
+                *t
+            } else {
+                return Err(ParseError::GenericError(self.info(token)));
+            };
+        }
+
+        match token.t {
+            TokenType::OpPlus => {
+                self.bump();
+
+                let next = if let Some(t) = self.tokens.peek() {
+                    *t
+                } else {
+                    return Err(ParseError::GenericError(self.info(token)));
+                };
+
+                match next.t {
+                    TokenType::FieldType => Ok(Transition::AddModel(self.next_model(doc)?)),
+                    TokenType::Enum => Ok(Transition::AddEnum(self.next_enum(doc)?)),
+                    _ => Err(ParseError::GenericError(self.info(next))),
+                }
+            },
+            TokenType::OpMinus => {
+                self.bump();
+
+                let keyword = match self.tokens.peek().copied() {
+                    Some(t) if t.t == TokenType::FieldType || t.t == TokenType::Enum => t,
+                    Some(t) => return Err(ParseError::GenericError(self.info(t))),
+                    None => return Err(ParseError::GenericError(self.info(token))),
+                };
+
+                self.bump();
+
+                let name = match self.tokens.peek().copied() {
+                    Some(t) if t.t == TokenType::Identifier => t,
+                    Some(t) => return Err(ParseError::GenericError(self.info(t))),
+                    None => return Err(ParseError::GenericError(self.info(keyword))),
+                };
+
+                self.bump();
+
+                let span = token.span.to(name.span);
+                if keyword.t == TokenType::FieldType {
+                    Ok(Transition::RemoveModel { name: name.value.clone(), span })
+                } else {
+                    Ok(Transition::RemoveEnum { name: name.value.clone(), span })
+                }
+            },
+            TokenType::FieldType => {
+                let (start, name, _, mut annotations) = self.next_header()?;
+                let rename_from = self.take_rename(&mut annotations)?;
+                let (clear_doc, clear_annotations) = self.take_clear(&mut annotations)?;
+                let fields = self.get_field_transitions()?;
+
+                Ok(Transition::ChangeModel(ModelTransition {
+                    name,
+                    rename_from,
+                    doc,
+                    annotations: if annotations.is_empty() { None } else { Some(annotations) },
+                    clear_doc,
+                    clear_annotations,
+                    fields,
+                    span: start.to(self.prev_span),
+                }))
+            },
+            TokenType::Enum => Ok(Transition::ReplaceEnum(self.next_enum(doc)?)),
+            _ => Err(ParseError::GenericError(self.info(token))),
+        }
+    }
+
+    fn get_field_transitions(&mut self) -> ParseResult<Vec<FieldTransition>> {
+        let mut transitions = Vec::new();
+
+        loop {
+            let mut token = if let Some(t) = self.tokens.peek() {
+                *t
+            } else {
+                return Err(ParseError::MissingRightBracketError(self.span_info(self.prev_span)));
+            };
+
+            if token.t == TokenType::CurlyR {
+                self.bump();
+                break;
+            }
+
+            // the doc string may come before or after the marker
+            let mut doc = None;
+
+            if token.t == TokenType::DocString {
+                doc = Some(token.value.clone());
+                self.bump();
+
+                token = if let Some(t) = self.tokens.peek() {
+                    *t
+                } else {
+                    return Err(ParseError::ExpectedFieldIdentifierError(self.info(token)));
+                };
+            }
+
+            match token.t {
+                TokenType::OpPlus => {
+                    self.bump();
+
+                    let mut field = self.next_field()?;
+                    if field.doc.is_none() {
+                        field.doc = doc;
+                    }
+                    transitions.push(FieldTransition::Add(field));
+                },
+                TokenType::OpMinus => {
+                    self.bump();
+
+                    let name = match self.tokens.peek().copied() {
+                        Some(t) if t.t == TokenType::Identifier => t,
+                        Some(t) => return Err(ParseError::ExpectedFieldIdentifierError(self.info(t))),
+                        None => return Err(ParseError::ExpectedFieldIdentifierError(self.info(token))),
+                    };
+
+                    self.bump();
+
+                    transitions.push(FieldTransition::Remove { name: name.value.clone(), span: token.span.to(name.span) });
+                },
+                _ => {
+                    let mut field = self.next_field()?;
+                    if field.doc.is_none() {
+                        field.doc = doc;
+                    }
+                    let rename_from = self.take_rename(&mut field.annotations)?;
+                    transitions.push(FieldTransition::Change { field, rename_from });
+                }
+            }
+        }
+
+        Ok(transitions)
+    }
+
+    // `@rename(from: "old")` belongs to the migration, not to the resulting definition
+    fn take_rename(&self, annotations: &mut Vec<Annotation>) -> ParseResult<Option<String>> {
+        let index = if let Some(i) = annotations.iter().position(|a| a.name == "rename") {
+            i
+        } else {
+            return Ok(None);
+        };
+
+        let rename = annotations.remove(index);
+
+        match rename.arg("from") {
+            Some(AnnotationValue::String(from)) | Some(AnnotationValue::Identifier(from)) => Ok(Some(from.clone())),
+            _ => Err(ParseError::GenericAnnotationError(self.span_info(rename.span), String::from("Expected `@rename(from: \"name\")`."))),
+        }
+    }
+
+    // `@clear(doc: true, annotations: true)` removes a model's doc string or annotations, which leaving them out can't
+    fn take_clear(&self, annotations: &mut Vec<Annotation>) -> ParseResult<(bool, bool)> {
+        let index = if let Some(i) = annotations.iter().position(|a| a.name == "clear") {
+            i
+        } else {
+            return Ok((false, false));
+        };
+
+        let clear = annotations.remove(index);
+        let (mut doc, mut all) = (false, false);
+
+        for arg in clear.args.iter() {
+            match (arg.name.as_str(), &arg.value) {
+                ("doc", AnnotationValue::Bool(b)) => doc = *b,
+                ("annotations", AnnotationValue::Bool(b)) => all = *b,
+                _ => return Err(ParseError::GenericAnnotationError(self.span_info(clear.span), String::from("Expected `@clear(doc: true, annotations: true)`."))),
+            }
+        }
+
+        Ok((doc, all))
+    }
+
+    // `type` or `enum` keyword followed by the name, any annotations and the opening curly bracket
+    fn next_header(&mut self) -> ParseResult<(Span, String, Span, Vec<Annotation>)> {
+        let mut token = if let Some(t) = self.bump() {
+            t
+        } else {
+            return Err(ParseError::UnknownError);
+        };
+        let start = token.span;
 
         token = if let Some(t) = self.tokens.peek() {
             *t
         } else {
             // expected Identifier but there was nothing
-            return Err(ParseError::GenericError(TokenInfo { loc: token.loc }))
+            return Err(ParseError::GenericError(self.info(token)))
         };
 
         if token.t != TokenType::Identifier {
             // expected Identifier but got <tokentype>
-            return Err(ParseError::GenericError(TokenInfo { loc: token.loc }))
+            return Err(ParseError::GenericError(self.info(token)))
         }
 
         let name = token.value.clone();
+        let name_span = token.span;
 
-        if self.model_identifiers.contains(&name) {
-            return Err(ParseError::DuplicateModelIdentifierError(TokenInfo { loc: token.loc }))
+        if type_name_taken(&self.model_identifiers, &name, &self.options) {
+            self.report(ParseError::DuplicateModelIdentifierError(self.info(token)))?;
+        } else {
+            self.model_identifiers.push(name.clone());
         }
 
+        self.bump();
 
-        self.tokens.next();
+        let annotations = self.parse_annotations()?;
 
         token = if let Some(t) = self.tokens.peek() {
             *t
         } else {
             // expected left curly bracket but got nothing
-            return Err(ParseError::GenericError(TokenInfo { loc: token.loc }))
+            return Err(ParseError::GenericError(self.info(token)))
         };
 
         if token.t != TokenType::CurlyL {
             // expected left curly bracket but got <tokentype>
-            return Err(ParseError::GenericError(TokenInfo { loc: token.loc }))
+            return Err(ParseError::GenericError(self.info(token)))
         }
 
-        self.tokens.next();
-
-        let fields = self.get_fields()?;
+        self.bump();
 
-        self.model_identifiers.push(name.clone());
-        Ok(Some(ModelTypeDef { name, fields, annotation }))
+        Ok((start, name, name_span, annotations))
     }
 
-    fn get_fields(&mut self) -> ParseResult<Vec<FieldDef>> {
-        let mut fields = Vec::new();
-        let mut field_names = Vec::new();
+    fn get_variants(&mut self) -> ParseResult<Vec<EnumVariant>> {
+        let mut variants: Vec<EnumVariant> = Vec::new();
 
         let mut token = if let Some(t) = self.tokens.peek() {
             *t
         } else {
-            return Err(ParseError::NoFieldsError);
+            return Err(ParseError::MissingRightBracketError(self.span_info(self.prev_span)));
         };
 
         while token.t != TokenType::CurlyR {
-            let mut annotation = None;
-
-            if token.t == TokenType::DocString {
-                annotation = Some(token.value.clone());
-                self.tokens.next();
+            let variant = match self.next_variant() {
+                Ok(v) => v,
+                Err(e) => {
+                    self.report(e)?;
+                    self.synchronize();
+                    return Ok(variants);
+                }
+            };
 
-                token = if let Some(t) = self.tokens.peek() {
-                    *t
-                } else {
-                    // no type for field
-                    return Err(ParseError::MissingFieldTypeError(TokenInfo { loc: token.loc }));
-                };
+            if variants.iter().any(|v| v.name == variant.name) {
+                self.report(ParseError::DuplicateEnumVariantError(self.span_info(variant.span)))?;
+            } else {
+                variants.push(variant);
             }
 
-            if token.t != TokenType::Identifier {
-                // identifier expected
-                return Err(ParseError::ExpectedFieldIdentifierError(TokenInfo { loc: token.loc }))
-            }
+            token = if let Some(t) = self.tokens.peek() {
+                *t
+            } else {
+                // no curly brace to end it
+                return Err(ParseError::MissingRightBracketError(self.span_info(self.prev_span)));
+            };
+        }
 
-            let name = token.value.clone();
+        self.bump();
 
-            if field_names.contains(&name) {
-                return Err(ParseError::DuplicateFieldIdentifierError(TokenInfo { loc: token.loc }))
-            }
+        Ok(variants)
+    }
 
-            self.tokens.next();
+    fn next_variant(&mut self) -> ParseResult<EnumVariant> {
+        let mut doc = None;
+        let mut token = if let Some(t) = self.tokens.peek() {
+            *t
+        } else {
+            return Err(ParseError::ExpectedEnumVariantError(self.span_info(self.prev_span)));
+        };
+
+        if token.t == TokenType::DocString {
+            doc = Some(token.value.clone());
+            self.bump();
 
             token = if let Some(t) = self.tokens.peek() {
                 *t
             } else {
-                // no type for field
-                return Err(ParseError::MissingFieldTypeError(TokenInfo { loc: token.loc }));
+                return Err(ParseError::ExpectedEnumVariantError(self.info(token)));
             };
+        }
 
-            if token.t != TokenType::Colon {
-                // need colon to indicate type
-                return Err(ParseError::MissingColonError(TokenInfo { loc: token.loc }));
-            }
+        if token.t != TokenType::Identifier {
+            return Err(ParseError::ExpectedEnumVariantError(self.info(token)));
+        }
 
-            self.tokens.next();
+        self.bump();
 
-            // FIELD TYPE
+        Ok(EnumVariant { name: token.value.clone(), doc, span: token.span })
+    }
 
-            let (field_type, type_type) = self.parse_token_type()?;
+    fn get_fields(&mut self) -> ParseResult<Vec<FieldDef>> {
+        let mut fields: Vec<FieldDef> = Vec::new();
+
+        let mut token = if let Some(t) = self.tokens.peek() {
+            *t
+        } else {
+            return Err(ParseError::NoFieldsError);
+        };
+
+        while token.t != TokenType::CurlyR {
+            let field = match self.next_field() {
+                Ok(f) => f,
+                Err(e) => {
+                    self.report(e)?;
+                    // give up on the rest of this model but keep what was parsed
+                    self.synchronize();
+                    return Ok(fields);
+                }
+            };
+
+            if field_name_taken(&fields, &field.name).is_some() {
+                self.report(ParseError::DuplicateFieldIdentifierError(self.span_info(field.name_span)))?;
+            } else {
+                fields.push(field);
+            }
 
             token = if let Some(t) = self.tokens.peek() {
                 *t
             } else {
                 // no curly brace to end it
-                return Err(ParseError::MissingRightBracketError(TokenInfo { loc: token.loc }));
+                return Err(ParseError::MissingRightBracketError(self.span_info(self.prev_span)));
             };
+        }
 
-            let mut field_required = false;
+        self.bump();
 
-            if token.t == TokenType::OpExclamation {
-                field_required = true;
+        Ok(fields)
+    }
+
+    fn next_field(&mut self) -> ParseResult<FieldDef> {
+        let mut doc = None;
+        let mut token = if let Some(t) = self.tokens.peek() {
+            *t
+        } else {
+            return Err(ParseError::ExpectedFieldIdentifierError(self.span_info(self.prev_span)));
+        };
 
-                self.tokens.next();
+        if token.t == TokenType::DocString {
+            doc = Some(token.value.clone());
+            self.bump();
 
-                token = if let Some(t) = self.tokens.peek() {
-                    *t
-                } else {
-                    // no curly brace to end it
-                    return Err(ParseError::MissingRightBracketError(TokenInfo { loc: token.loc }));
-                };
+            token = if let Some(t) = self.tokens.peek() {
+                *t
+            } else {
+                // no type for field
+                return Err(ParseError::MissingFieldTypeError(self.info(token)));
+            };
+        }
+
+        if token.t != TokenType::Identifier {
+            // identifier expected
+            return Err(ParseError::ExpectedFieldIdentifierError(self.info(token)))
+        }
+
+        let name = token.value.clone();
+        let name_span = token.span;
+
+        self.bump();
+
+        token = if let Some(t) = self.tokens.peek() {
+            *t
+        } else {
+            // no type for field
+            return Err(ParseError::MissingFieldTypeError(self.info(token)));
+        };
+
+        if token.t != TokenType::Colon {
+            // need colon to indicate type
+            return Err(ParseError::MissingColonError(self.info(token)));
+        }
+
+        self.bump();
+
+        // FIELD TYPE
+
+        let (field_type, type_type) = self.parse_token_type()?;
+
+        token = if let Some(t) = self.tokens.peek() {
+            *t
+        } else {
+            // no curly brace to end it
+            return Err(ParseError::MissingRightBracketError(self.info(token)));
+        };
+
+        let mut field_required = false;
+
+        if token.t == TokenType::OpExclamation {
+            field_required = true;
+
+            self.bump();
+        }
+
+        let relation = self.parse_relation()?;
+        let annotations = self.parse_annotations()?;
+
+        // CONSTRUCT FIELD
+
+        Ok(FieldDef { name, field_type, type_type, required: field_required, doc, annotations, relation, span: name_span.to(self.prev_span), name_span })
+    }
+
+    // optional `-> inverseField` naming the field on the other side of the relation
+    fn parse_relation(&mut self) -> ParseResult<Option<Relation>> {
+        let arrow = match self.tokens.peek().copied() {
+            Some(t) if t.t == TokenType::OpArrow => t,
+            _ => return Ok(None),
+        };
+
+        self.bump();
+
+        let token = if let Some(t) = self.tokens.peek() {
+            *t
+        } else {
+            return Err(ParseError::InvalidRelationError(self.info(arrow), String::from("Expected the name of the inverse field after '->'.")));
+        };
+
+        if token.t != TokenType::Identifier {
+            return Err(ParseError::InvalidRelationError(self.info(token), String::from("Expected the name of the inverse field after '->'.")));
+        }
+
+        self.bump();
+
+        Ok(Some(Relation { inverse: token.value.clone(), inverse_span: token.span, cardinality: None, span: arrow.span.to(token.span) }))
+    }
+
+    // zero or more `@name` or `@name(arg: value, ...)`
+    fn parse_annotations(&mut self) -> ParseResult<Vec<Annotation>> {
+        let mut annotations = Vec::new();
+
+        while let Some(at) = self.tokens.peek().copied() {
+            if at.t != TokenType::At {
+                break;
             }
 
-            // CONSTRUCT FIELD
+            self.bump();
+
+            let token = self.expect_annotation_token(at, "Expected an annotation name after '@'.")?;
+            if !token_is_name(token) {
+                return Err(ParseError::GenericAnnotationError(self.info(token), String::from("Expected an annotation name after '@'.")));
+            }
+            self.bump();
+
+            let name = token.value.clone();
+            let mut args = Vec::new();
+
+            if self.tokens.peek().map(|t| t.t == TokenType::ParenL) == Some(true) {
+                self.bump();
+
+                loop {
+                    let token = self.expect_annotation_token(token, "Missing closing parenthesis on annotation.")?;
+
+                    if token.t == TokenType::ParenR {
+                        self.bump();
+                        break;
+                    }
+
+                    if !token_is_name(token) {
+                        return Err(ParseError::GenericAnnotationError(self.info(token), String::from("Expected an argument name.")));
+                    }
+                    self.bump();
+
+                    let colon = self.expect_annotation_token(token, "Missing colon after argument name.")?;
+                    if colon.t != TokenType::Colon {
+                        return Err(ParseError::GenericAnnotationError(self.info(colon), String::from("Missing colon after argument name.")));
+                    }
+                    self.bump();
+
+                    let value = self.parse_annotation_value(colon)?;
+                    args.push(AnnotationArg { name: token.value.clone(), value });
+
+                    let next = self.expect_annotation_token(colon, "Missing closing parenthesis on annotation.")?;
+                    match next.t {
+                        TokenType::Comma => {
+                            self.bump();
+                        },
+                        TokenType::ParenR => {},
+                        _ => return Err(ParseError::GenericAnnotationError(self.info(next), String::from("Expected ',' or ')' after argument."))),
+                    }
+                }
+            }
 
-            field_names.push(name.clone());
-            fields.push(FieldDef { name, field_type, type_type, required: field_required, annotation });
+            annotations.push(Annotation { name, args, span: at.span.to(self.prev_span) });
         }
-       
-        self.tokens.next();
 
-        Ok(fields)
+        Ok(annotations)
+    }
+
+    fn parse_annotation_value(&mut self, previous: &Token) -> ParseResult<AnnotationValue> {
+        let token = self.expect_annotation_token(previous, "Expected an argument value.")?;
+
+        let value = match token.t {
+            TokenType::StringLiteral => AnnotationValue::String(token.value.clone()),
+            TokenType::Number | TokenType::OpMinus => {
+                let mut text = String::new();
+                let mut number = token;
+
+                if token.t == TokenType::OpMinus {
+                    self.bump();
+                    number = self.expect_annotation_token(token, "Expected a number after '-'.")?;
+                    if number.t != TokenType::Number {
+                        return Err(ParseError::GenericAnnotationError(self.info(number), String::from("Expected a number after '-'.")));
+                    }
+                    text.push('-');
+                }
+
+                text.push_str(&number.value);
+
+                if let Ok(i) = text.parse::<i64>() {
+                    AnnotationValue::Int(i)
+                } else if let Ok(f) = text.parse::<f64>() {
+                    AnnotationValue::Float(f)
+                } else {
+                    return Err(ParseError::GenericAnnotationError(self.info(number), String::from("Number is out of range.")));
+                }
+            },
+            TokenType::BracketL => {
+                self.bump();
+                let mut values = Vec::new();
+
+                loop {
+                    let next = self.expect_annotation_token(token, "Missing closing bracket on list.")?;
+
+                    if next.t == TokenType::BracketR {
+                        break;
+                    }
+
+                    values.push(self.parse_annotation_value(next)?);
+
+                    let next = self.expect_annotation_token(token, "Missing closing bracket on list.")?;
+                    match next.t {
+                        TokenType::Comma => {
+                            self.bump();
+                        },
+                        TokenType::BracketR => {},
+                        _ => return Err(ParseError::GenericAnnotationError(self.info(next), String::from("Expected ',' or ']' in list."))),
+                    }
+                }
+
+                AnnotationValue::List(values)
+            },
+            _ if token_is_name(token) => match token.value.as_ref() {
+                "true" => AnnotationValue::Bool(true),
+                "false" => AnnotationValue::Bool(false),
+                _ => AnnotationValue::Identifier(token.value.clone()),
+            },
+            _ => return Err(ParseError::GenericAnnotationError(self.info(token), String::from("Expected an argument value."))),
+        };
+
+        self.bump();
+
+        Ok(value)
+    }
+
+    // peeks the next token, running out of tokens inside an annotation is an error
+    fn expect_annotation_token(&mut self, previous: &Token, message: &str) -> ParseResult<&'a Token> {
+        match self.tokens.peek().copied() {
+            Some(t) if t.t != TokenType::EOF => Ok(t),
+            Some(t) => Err(ParseError::GenericAnnotationError(self.info(t), String::from(message))),
+            None => Err(ParseError::GenericAnnotationError(self.info(previous), String::from(message))),
+        }
     }
 
     fn parse_token_type(&mut self) -> ParseResult<(FieldType, FieldTypeType)> {
@@ -215,68 +780,152 @@ impl<'a> Parser<'a> {
         if token.t == TokenType::BracketL {
             type_type = FieldTypeType::Array;
 
-            self.tokens.next();
+            self.bump();
 
             token = if let Some(t) = self.tokens.peek() {
                 *t
             } else {
-                return Err(ParseError::GenericFieldParsingError(Some(TokenInfo { loc: token.loc }), String::from("Could not get next token after opening array bracket.")));
+                return Err(ParseError::GenericFieldParsingError(Some(self.info(token)), String::from("Could not get next token after opening array bracket.")));
             };
         }
 
-        if token_is_type(&token) {
-            field_type = FieldType::Scalar(token.value.clone());
+        if token_is_type(token) || (token.t == TokenType::Identifier && self.options.scalars.contains(&token.value)) {
+            field_type = FieldType::Scalar(token.value.clone(), token.span);
         }
         else if token.t == TokenType::Identifier {
-            field_type = FieldType::Identfier(token.value.clone());
-            self.referenced_identifiers.push((TokenInfo { loc: token.loc }, token.value.clone()))
+            field_type = FieldType::Identfier(token.value.clone(), token.span);
+            self.referenced_identifiers.push((self.info(token), token.value.clone()))
         }
         else {
-            return Err(ParseError::GenericFieldParsingError(Some(TokenInfo { loc: token.loc }), String::from("Field type could not be identified as scalar or identifier.")));
+            return Err(ParseError::GenericFieldParsingError(Some(self.info(token)), String::from("Field type could not be identified as scalar or identifier.")));
         }
 
-        self.tokens.next();
+        self.bump();
 
         token = if let Some(t) = self.tokens.peek() {
             *t
         } else {
-            return Err(ParseError::GenericFieldParsingError(Some(TokenInfo { loc: token.loc }), String::from("Could not get next token after getting field type.")));
+            return Err(ParseError::GenericFieldParsingError(Some(self.info(token)), String::from("Could not get next token after getting field type.")));
         };
 
         if type_type == FieldTypeType::Array || type_type == FieldTypeType::RequiredArray {
             if token.t == TokenType::OpExclamation {
                 type_type = FieldTypeType::RequiredArray;
     
-                self.tokens.next();
+                self.bump();
     
                 token = if let Some(t) = self.tokens.peek() {
                     *t
                 } else {
-                    return Err(ParseError::GenericFieldParsingError(Some(TokenInfo { loc: token.loc }), String::from("Could not get next token after required array field type found.")));
+                    return Err(ParseError::GenericFieldParsingError(Some(self.info(token)), String::from("Could not get next token after required array field type found.")));
                 };
             }
 
             if token.t == TokenType::BracketR {
-                self.tokens.next();
+                self.bump();
             } else {
-                return Err(ParseError::GenericFieldParsingError(Some(TokenInfo { loc: token.loc }), String::from("Missing closing right bracket on field.")));
+                return Err(ParseError::GenericFieldParsingError(Some(self.info(token)), String::from("Missing closing right bracket on field.")));
             }
         }
 
         Ok((field_type, type_type))
     }
 
-    // reconcile types referenced within a field. 
+    fn bump(&mut self) -> Option<&'a Token> {
+        let next = self.tokens.next();
+        if let Some(t) = next {
+            self.prev_span = t.span;
+        }
+        next
+    }
+
+    fn info(&self, token: &Token) -> TokenInfo {
+        self.span_info(token.span)
+    }
+
+    fn span_info(&self, span: Span) -> TokenInfo {
+        TokenInfo { span, line_col: self.line_index.line_col(span.start) }
+    }
+
+    // reconcile types referenced within a field.
     // make sure the type referenced is defined within the file.
-    fn check_identifiers(&mut self) -> ParseResult<()> {
-        for ref_id in self.referenced_identifiers.iter() {
-            if !self.model_identifiers.contains(&ref_id.1) {
-                return Err(ParseError::MissingModelTypeError(ref_id.0.clone()))
+    fn check_identifiers(&mut self) -> Vec<ParseError> {
+        self.referenced_identifiers.iter()
+            .filter(|ref_id| !self.model_identifiers.contains(&ref_id.1))
+            .map(|ref_id| ParseError::MissingModelTypeError(ref_id.0.clone()))
+            .collect()
+    }
+
+    // make sure both sides of every relation line up and work out the cardinality
+    fn check_relations(&self, root: &mut Root) -> Vec<ParseError> {
+        let mut errors = Vec::new();
+        let mut cardinalities = Vec::new();
+
+        for (m, model) in root.types.iter().enumerate() {
+            for (f, field) in model.fields.iter().enumerate() {
+                let inverse_span = field.relation.as_ref().map_or(field.name_span, |r| r.inverse_span);
+
+                let inverse = match root.check_relation(model, field) {
+                    Ok(Some(i)) => i,
+                    // unknown types are reported by `check_identifiers`
+                    Ok(None) | Err(RelationError::UnknownTypeError) => continue,
+                    Err(RelationError::MissingInverseError) => {
+                        errors.push(ParseError::MissingInverseFieldError(self.span_info(inverse_span)));
+                        continue;
+                    },
+                    Err(e @ RelationError::ScalarTypeError) | Err(e @ RelationError::EnumTypeError) => {
+                        errors.push(ParseError::InvalidRelationError(self.span_info(field.field_type.span()), e.to_string()));
+                        continue;
+                    },
+                    Err(e) => {
+                        errors.push(ParseError::InvalidRelationError(self.span_info(inverse_span), e.to_string()));
+                        continue;
+                    },
+                };
+
+                cardinalities.push((m, f, Cardinality::between(field, inverse)));
+            }
+        }
+
+        for (m, f, cardinality) in cardinalities {
+            if let Some(r) = root.types[m].fields[f].relation.as_mut() {
+                r.cardinality = Some(cardinality);
             }
         }
 
-        Ok(())
+        errors
+    }
+
+    // errors are collected while recovering, otherwise they are returned straight away
+    fn report(&mut self, error: ParseError) -> ParseResult<()> {
+        if self.recover {
+            self.errors.push(error);
+            Ok(())
+        } else {
+            Err(error)
+        }
     }
+
+    // skip tokens until the start of the next model or past the end of the current one
+    fn synchronize(&mut self) {
+        while let Some(token) = self.tokens.peek() {
+            match token.t {
+                TokenType::FieldType | TokenType::Enum | TokenType::EOF => break,
+                TokenType::CurlyR => {
+                    self.bump();
+                    break;
+                },
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+    }
+}
+
+// keywords are allowed as annotation and argument names
+fn token_is_name(token: &Token) -> bool {
+    matches!(token.t, TokenType::Identifier | TokenType::FieldType | TokenType::Enum | TokenType::TInt | TokenType::TString)
 }
 
 fn token_is_type(token: &Token) -> bool {
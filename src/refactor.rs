@@ -0,0 +1,138 @@
+use crate::{
+    _helpers::{RenameError, RenameResult},
+    ast::{self, Root, ModelTypeDef},
+    lexer::{self, Lexer, Token, Tokens, TokenType},
+    parser::{self, Parser, ParseOptions},
+    span::Span,
+};
+
+// replaces the text covered by `span`, edits never overlap
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub new_text: String,
+}
+
+// renames a model or an enum, its declaration and every field type naming it.
+// only names are touched so formatting and comments stay as they are
+pub fn rename_type(src: &str, old: &str, new: &str) -> RenameResult<Vec<TextEdit>> {
+    rename_type_with_options(src, old, new, &ParseOptions::default())
+}
+
+// the project's custom scalars resolve while parsing and can't be taken by the new name
+pub fn rename_type_with_options(src: &str, old: &str, new: &str, options: &ParseOptions) -> RenameResult<Vec<TextEdit>> {
+    check_name(new)?;
+
+    let tokens = Lexer::new(src).run();
+    let mut parser = Parser::with_options(&tokens, options.clone());
+    let root = parser.parse().map_err(RenameError::SchemaError)?;
+
+    let declaration = declaration_span(&root, old).ok_or_else(|| RenameError::UnknownTypeError(old.to_string()))?;
+    if old == new {
+        return Ok(Vec::new());
+    }
+    if parser::type_name_taken(root.type_names(), new, options) {
+        return Err(match declaration_span(&root, new) {
+            Some(existing) => RenameError::DuplicateTypeError(new.to_string(), existing),
+            None => RenameError::ScalarNameError(new.to_string()),
+        });
+    }
+
+    let mut spans = vec![declaration];
+    spans.extend(parser.referenced_identifiers().iter().filter(|(_, name)| name == old).map(|(info, _)| info.span));
+
+    Ok(edits(spans, new))
+}
+
+// renames a field, the relations on other models that name it as their inverse
+// and the model's annotations listing it, e.g. `@index(fields: ["title"])`
+pub fn rename_field(src: &str, model: &str, old: &str, new: &str) -> RenameResult<Vec<TextEdit>> {
+    rename_field_with_options(src, model, old, new, &ParseOptions::default())
+}
+
+pub fn rename_field_with_options(src: &str, model: &str, old: &str, new: &str, options: &ParseOptions) -> RenameResult<Vec<TextEdit>> {
+    check_name(new)?;
+
+    let tokens = Lexer::new(src).run();
+    let root = Parser::with_options(&tokens, options.clone()).parse().map_err(RenameError::SchemaError)?;
+
+    let model_def = root.model(model).ok_or_else(|| RenameError::UnknownTypeError(model.to_string()))?;
+    let field = ast::field(&model_def.fields, old).ok_or_else(|| RenameError::UnknownFieldError(model.to_string(), old.to_string()))?;
+    if old == new {
+        return Ok(Vec::new());
+    }
+    if let Some(existing) = parser::field_name_taken(&model_def.fields, new) {
+        return Err(RenameError::DuplicateFieldError(model.to_string(), new.to_string(), existing.name_span));
+    }
+
+    let mut spans = vec![field.name_span];
+    for other in root.types.iter().flat_map(|m| m.fields.iter()) {
+        if let Some(relation) = &other.relation {
+            if other.field_type.name() == model && relation.inverse == old {
+                spans.push(relation.inverse_span);
+            }
+        }
+    }
+
+    let mut edits = edits(spans, new);
+    edits.extend(field_list_spans(&tokens, model_def, old).into_iter().map(|span| TextEdit { span, new_text: format!("\"{}\"", new) }));
+    edits.sort_by_key(|e| e.span.start);
+
+    Ok(edits)
+}
+
+// applies edits made against `src`, in any order
+pub fn apply_edits(src: &str, edits: &[TextEdit]) -> String {
+    let mut edits: Vec<&TextEdit> = edits.iter().collect();
+    edits.sort_by_key(|e| e.span.start);
+
+    let mut out = String::new();
+    let mut last = 0;
+    for edit in edits {
+        out.push_str(&src[last..edit.span.start]);
+        out.push_str(&edit.new_text);
+        last = edit.span.end;
+    }
+    out.push_str(&src[last..]);
+
+    out
+}
+
+fn declaration_span(root: &Root, name: &str) -> Option<Span> {
+    root.model(name).map(|m| m.name_span).or_else(|| root.enum_def(name).map(|e| e.name_span))
+}
+
+fn check_name(name: &str) -> RenameResult<()> {
+    if lexer::is_identifier(name) {
+        Ok(())
+    } else {
+        Err(RenameError::InvalidNameError(name.to_string()))
+    }
+}
+
+// string literals naming `old` in a `fields` argument of the model's annotations, quotes included
+fn field_list_spans(tokens: &Tokens, model: &ModelTypeDef, old: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+
+    for annotation in model.annotations.iter() {
+        let inside = |t: &&Token| t.span.start >= annotation.span.start && t.span.end <= annotation.span.end;
+        let mut argument = None;
+        let mut previous: Option<&Token> = None;
+
+        for token in tokens.into_iter().filter(inside) {
+            if token.t == TokenType::Colon {
+                argument = previous.map(|p| p.value.as_str());
+            } else if token.t == TokenType::StringLiteral && argument == Some("fields") && token.value == old {
+                spans.push(token.span);
+            }
+            previous = Some(token);
+        }
+    }
+
+    spans
+}
+
+fn edits(mut spans: Vec<Span>, new: &str) -> Vec<TextEdit> {
+    spans.sort_by_key(|s| s.start);
+    spans.into_iter().map(|span| TextEdit { span, new_text: new.to_string() }).collect()
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+
+// byte offsets into the original source, `end` is exclusive
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    // smallest span covering both
+    pub fn to(&self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+// 1 based line and column, column is counted in chars
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for LineCol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+
+// maps byte offsets back to line/column positions
+#[derive(Clone, Debug, Default)]
+pub struct LineIndex {
+    src: String,
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(src: &str) -> LineIndex {
+        let mut line_starts = vec![0];
+
+        for (i, c) in src.char_indices() {
+            if c == '\n' {
+                line_starts.push(i + 1);
+            }
+        }
+
+        LineIndex { src: src.to_string(), line_starts }
+    }
+
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let offset = offset.min(self.src.len());
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(l) => l,
+            Err(l) => l - 1,
+        };
+        let line_start = self.line_starts[line];
+        let col = self.src.get(line_start..offset).map_or(offset - line_start, |s| s.chars().count());
+
+        LineCol { line: line + 1, col: col + 1 }
+    }
+
+    // inverse of `line_col`, `None` when the position is outside of the source
+    pub fn offset(&self, pos: LineCol) -> Option<usize> {
+        if pos.line == 0 || pos.col == 0 {
+            return None;
+        }
+
+        let line_start = *self.line_starts.get(pos.line - 1)?;
+        let line = self.line(pos.line)?;
+
+        if pos.col - 1 == line.chars().count() {
+            return Some(line_start + line.len());
+        }
+
+        line.char_indices().nth(pos.col - 1).map(|(i, _)| line_start + i)
+    }
+
+    // text of a 1 based line without the line ending
+    pub fn line(&self, line: usize) -> Option<&str> {
+        if line == 0 {
+            return None;
+        }
+
+        let start = *self.line_starts.get(line - 1)?;
+        let end = self.line_starts.get(line).map_or(self.src.len(), |e| *e);
+
+        Some(self.src[start..end].trim_end_matches(&['\n', '\r'][..]))
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    pub fn source(&self) -> &str {
+        &self.src
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_cols() {
+        let index = LineIndex::new("type Movie {\n    name: String\r\n}");
+
+        assert_eq!(index.line_col(0), LineCol { line: 1, col: 1 });
+        assert_eq!(index.line_col(5), LineCol { line: 1, col: 6 });
+        assert_eq!(index.line_col(17), LineCol { line: 2, col: 5 });
+        assert_eq!(index.line_col(31), LineCol { line: 3, col: 1 });
+        assert_eq!(index.line(2), Some("    name: String"));
+        assert_eq!(index.line_count(), 3);
+    }
+
+    #[test]
+    fn offsets_round_trip() {
+        let src = "é: Int\n  ü: String";
+        let index = LineIndex::new(src);
+
+        for (offset, _) in src.char_indices() {
+            assert_eq!(index.offset(index.line_col(offset)), Some(offset));
+        }
+
+        assert_eq!(index.line_col(2), LineCol { line: 1, col: 2 });
+        assert_eq!(index.offset(LineCol { line: 3, col: 1 }), None);
+    }
+}
@@ -0,0 +1,275 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn odetto_cli(args: &[&str]) -> (i32, String) {
+  let output = Command::new(env!("CARGO_BIN_EXE_odetto-cli"))
+    .args(args)
+    .output()
+    .expect("could not run odetto-cli");
+
+  (output.status.code().unwrap_or(-1), String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn odetto_cli_stdin(args: &[&str], input: &str) -> (i32, String) {
+  let mut child = Command::new(env!("CARGO_BIN_EXE_odetto-cli"))
+    .args(args)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .expect("could not run odetto-cli");
+  child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+  let output = child.wait_with_output().unwrap();
+
+  (output.status.code().unwrap_or(-1), String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[test]
+fn check_compat_breaking() {
+  let (code, stdout) = odetto_cli(&["check-compat", "tests/odet-files/17-migration-base.odet", "tests/odet-files/19-diff-new.odet"]);
+  println!("{}", stdout);
+
+  assert_eq!(code, 1);
+  assert!(stdout.contains("breaking: model `Film` was removed\n"));
+}
+
+#[test]
+fn check_compat_safe() {
+  let (code, stdout) = odetto_cli(&["check-compat", "tests/odet-files/17-migration-base.odet", "tests/odet-files/17-migration-base.odet"]);
+
+  assert_eq!(code, 0);
+  assert_eq!(stdout, "0 breaking, 0 risky, 0 safe\n");
+}
+
+#[test]
+fn check_compat_errors() {
+  assert_eq!(odetto_cli(&["check-compat", "tests/odet-files/17-migration-base.odet"]).0, 2);
+  assert_eq!(odetto_cli(&["check-compat", "tests/odet-files/7-missing-type-parser.odet", "tests/odet-files/2-happy-parser.odet"]).0, 2);
+  assert_eq!(odetto_cli(&["check-compat", "--consumer", "nobody", "a.odet", "b.odet"]).0, 2);
+}
+
+#[test]
+fn fmt_check() {
+  let (code, stdout) = odetto_cli(&["fmt", "--check", "tests/odet-files/21-format.odet", "tests/snapshots/21-format.odet"]);
+
+  assert_eq!(code, 1);
+  assert_eq!(stdout, "tests/odet-files/21-format.odet\n");
+  assert_eq!(odetto_cli(&["fmt", "--check", "tests/snapshots/21-format.odet"]).0, 0);
+  assert_eq!(odetto_cli(&["fmt", "--check", "tests/odet-files/7-missing-type-parser.odet"]).0, 2);
+  assert_eq!(odetto_cli(&["fmt"]).0, 2);
+}
+
+#[test]
+fn fmt_rewrites_files() {
+  let path = std::env::temp_dir().join(format!("odetto-fmt-{}.odet", std::process::id()));
+  std::fs::copy("tests/odet-files/21-format.odet", &path).unwrap();
+
+  let (code, _) = odetto_cli(&["fmt", path.to_str().unwrap()]);
+  let formatted = std::fs::read_to_string(&path).unwrap();
+  std::fs::remove_file(&path).unwrap();
+
+  assert_eq!(code, 0);
+  assert_eq!(formatted, std::fs::read_to_string("tests/snapshots/21-format.odet").unwrap());
+}
+
+#[test]
+fn usage() {
+  assert_eq!(odetto_cli(&[]).0, 2);
+  assert_eq!(odetto_cli(&["frobnicate"]).0, 2);
+
+  let (code, stdout) = odetto_cli(&["help"]);
+  assert_eq!(code, 0);
+  assert!(stdout.starts_with("usage: odetto-cli <command>"));
+}
+
+#[test]
+fn check() {
+  assert_eq!(odetto_cli(&["check", "tests/odet-files/2-happy-parser.odet", "tests/odet-files/20-codegen.odet"]).0, 0);
+  assert_eq!(odetto_cli(&["check", "tests/odet-files/7-missing-type-parser.odet", "tests/odet-files/2-happy-parser.odet"]).0, 1);
+  assert_eq!(odetto_cli(&["check", "tests/odet-files/missing.odet"]).0, 2);
+  assert_eq!(odetto_cli_stdin(&["check"], "type A { b: Int }").0, 0);
+  assert_eq!(odetto_cli_stdin(&["check"], "type A { b: C }").0, 1);
+  assert_eq!(odetto_cli_stdin(&["check", "-"], "type A { b: C }").0, 1);
+}
+
+#[test]
+fn tokens_and_ast() {
+  let (code, stdout) = odetto_cli_stdin(&["tokens"], "type A { b: Int }");
+  assert_eq!(code, 0);
+  assert!(stdout.contains("\tFieldType: 1:1 (0..4), value = 'type'\n"));
+
+  let (code, stdout) = odetto_cli(&["ast", "tests/odet-files/2-happy-parser.odet", "--format", "debug"]);
+  assert_eq!(code, 0);
+  assert!(stdout.starts_with("Root {\n    types: [\n        ModelTypeDef {\n            name: \"Movie\",\n"));
+
+  assert_eq!(odetto_cli(&["ast", "tests/odet-files/7-missing-type-parser.odet"]).0, 2);
+  assert_eq!(odetto_cli(&["ast", "--format", "xml"]).0, 2);
+  assert_eq!(odetto_cli(&["ast", "a.odet", "b.odet"]).0, 2);
+}
+
+#[test]
+fn generate() {
+  let (code, stdout) = odetto_cli(&["generate", "--target", "graphql", "tests/odet-files/20-codegen.odet"]);
+  assert_eq!(code, 0);
+  assert!(stdout.contains("type Post {\n"));
+
+  let out = std::env::temp_dir().join(format!("odetto-generate-{}", std::process::id()));
+  let (code, _) = odetto_cli(&["generate", "--target", "typescript", "--out", out.to_str().unwrap(), "tests/odet-files/20-codegen.odet"]);
+  let written = std::fs::read_to_string(out.join("index.d.ts")).unwrap();
+  std::fs::remove_dir_all(&out).unwrap();
+  assert_eq!(code, 0);
+  assert!(written.contains("export interface User {\n"));
+
+  let (code, stdout) = odetto_cli(&["generate", "--target", "rust", "--option", "derives=", "--option", "serde=true", "tests/odet-files/20-codegen.odet"]);
+  assert_eq!(code, 0);
+  assert!(stdout.contains("#[derive(serde::Serialize, serde::Deserialize)]\npub struct User {\n"));
+
+  assert_eq!(odetto_cli(&["generate", "--target", "rust", "--option", "serde", "tests/odet-files/20-codegen.odet"]).0, 2);
+  assert_eq!(odetto_cli(&["generate", "--target", "postgres", "--option", "serde=true", "tests/odet-files/20-codegen.odet"]).0, 2);
+  assert_eq!(odetto_cli(&["generate", "tests/odet-files/20-codegen.odet"]).0, 2);
+  assert_eq!(odetto_cli(&["generate", "--target", "cobol", "tests/odet-files/20-codegen.odet"]).0, 2);
+  assert_eq!(odetto_cli(&["generate", "--target"]).0, 2);
+}
+
+#[test]
+fn fmt_stdin() {
+  let (code, stdout) = odetto_cli_stdin(&["fmt", "-"], "type A{b:Int}");
+  assert_eq!(code, 0);
+  assert_eq!(stdout, "type A {\n    b: Int\n}\n");
+
+  assert_eq!(odetto_cli_stdin(&["fmt", "--check", "-"], "type A{b:Int}").0, 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn ast_json_and_yaml() {
+  let (code, stdout) = odetto_cli(&["ast", "--format", "json", "tests/odet-files/22-serde.odet"]);
+  assert_eq!(code, 0);
+  assert_eq!(stdout, std::fs::read_to_string("tests/snapshots/22-serde.json").unwrap());
+
+  let (code, stdout) = odetto_cli_stdin(&["ast", "--format", "yaml"], "type A { b: Int }");
+  assert_eq!(code, 0);
+  assert!(stdout.starts_with("types:\n- name: A\n  fields:\n  - name: b\n    field_type:\n      kind: scalar\n"));
+}
+
+#[cfg(not(feature = "serde"))]
+#[test]
+fn ast_json_needs_serde() {
+  assert_eq!(odetto_cli(&["ast", "--format", "json", "tests/odet-files/2-happy-parser.odet"]).0, 2);
+}
+
+// runs with the test plugins on PATH
+#[cfg(all(unix, feature = "serde"))]
+fn odetto_cli_plugins(args: &[&str], request: &std::path::Path) -> (i32, String, String) {
+  let plugins = std::fs::canonicalize("tests/plugins").unwrap();
+  let path = format!("{}:{}", plugins.display(), std::env::var("PATH").unwrap_or_default());
+  let output = Command::new(env!("CARGO_BIN_EXE_odetto-cli"))
+    .args(args)
+    .env("PATH", path)
+    .env("ODETTO_REQUEST", request)
+    .output()
+    .expect("could not run odetto-cli");
+
+  (output.status.code().unwrap_or(-1), String::from_utf8_lossy(&output.stdout).to_string(), String::from_utf8_lossy(&output.stderr).to_string())
+}
+
+#[cfg(all(unix, feature = "serde"))]
+#[test]
+fn generate_with_plugins() {
+  let tmp = std::env::temp_dir().join(format!("odetto-plugins-{}", std::process::id()));
+  std::fs::create_dir_all(&tmp).unwrap();
+  let request = tmp.join("request.json");
+  let out = tmp.join("out");
+
+  let (code, _, stderr) = odetto_cli_plugins(&["generate", "--target", "echo", "--option", "style=short", "--out", out.to_str().unwrap(), "tests/odet-files/20-codegen.odet"], &request);
+  let written = std::fs::read_to_string(out.join("models/list.txt")).unwrap();
+  let sent: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&request).unwrap()).unwrap();
+  println!("{}", stderr);
+
+  assert_eq!(code, 0);
+  assert_eq!(written, "User\nPost\n");
+  assert!(stderr.starts_with("warning[echo]: enums are skipped\n  --> tests/odet-files/20-codegen.odet:45:6\n"));
+  assert_eq!(sent["version"], serde_json::json!(1));
+  assert_eq!(sent["options"], serde_json::json!({ "style": "short" }));
+  assert_eq!(sent["schema"]["enums"][0]["name"], serde_json::json!("Role"));
+
+  let (code, _, stderr) = odetto_cli_plugins(&["generate", "--target", "failing", "--out", out.to_str().unwrap(), "tests/odet-files/20-codegen.odet"], &request);
+  assert_eq!(code, 1);
+  assert!(stderr.starts_with("error[P001]: User needs a primary key\n"));
+  assert!(!out.join("never.txt").exists());
+
+  assert_eq!(odetto_cli_plugins(&["generate", "--target", "broken", "tests/odet-files/20-codegen.odet"], &request).0, 2);
+  assert_eq!(odetto_cli_plugins(&["generate", "--target", "nothing", "tests/odet-files/20-codegen.odet"], &request).0, 2);
+
+  std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+// runs in a copy of a project under tests/projects, odetto-cli finds the config from the current directory
+#[cfg(feature = "config")]
+fn odetto_cli_in(dir: &std::path::Path, args: &[&str]) -> (i32, String, String) {
+  let output = Command::new(env!("CARGO_BIN_EXE_odetto-cli"))
+    .args(args)
+    .current_dir(dir)
+    .output()
+    .expect("could not run odetto-cli");
+
+  (output.status.code().unwrap_or(-1), String::from_utf8_lossy(&output.stdout).to_string(), String::from_utf8_lossy(&output.stderr).to_string())
+}
+
+#[cfg(feature = "config")]
+fn copy_dir(from: &std::path::Path, to: &std::path::Path) {
+  std::fs::create_dir_all(to).unwrap();
+  for entry in std::fs::read_dir(from).unwrap() {
+    let entry = entry.unwrap();
+    if entry.file_type().unwrap().is_dir() {
+      copy_dir(&entry.path(), &to.join(entry.file_name()));
+    } else {
+      std::fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+    }
+  }
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn project_config() {
+  let project = std::env::temp_dir().join(format!("odetto-project-{}", std::process::id()));
+  copy_dir(std::path::Path::new("tests/projects/shop"), &project);
+
+  // the schema files are checked together and the lint rules from odetto.yml run on them
+  let (code, _, stderr) = odetto_cli_in(&project, &["check"]);
+  assert_eq!(code, 0);
+  assert!(stderr.starts_with("warning[missing-doc]: enum `Status` has no doc string\n  --> schema/orders.odet:11:6\n"), "{}", stderr);
+
+  let (code, stdout, _) = odetto_cli_in(&project.join("schema"), &["generate"]);
+  assert_eq!(code, 0);
+  assert!(stdout.starts_with("postgres: 1 file written to "), "{}", stdout);
+  assert!(stdout.contains("typescript: 3 files written to "));
+  let sql = std::fs::read_to_string(project.join("gen/sql/schema.sql")).unwrap();
+  assert!(sql.contains("    \"joined_at\" TEXT NOT NULL\n"));
+  let order = std::fs::read_to_string(project.join("gen/ts/Order.d.ts")).unwrap();
+  assert!(order.contains("    placedAt?: string;\n"));
+
+  // custom scalars also apply to files named on the command line
+  std::fs::write(project.join("event.odet"), "type Event { at: DateTime! }").unwrap();
+  let (code, stdout, _) = odetto_cli_in(&project, &["generate", "--target", "graphql", "event.odet"]);
+  assert_eq!(code, 0);
+  assert!(stdout.starts_with("scalar DateTime\n\ntype Event {\n"), "{}", stdout);
+
+  // errors point into the file they are in
+  std::fs::write(project.join("schema/orders.odet"), "type Order {\n    id: Int!\n    customer: Customer! -> orders\n    placedAt: Date\n}\n").unwrap();
+  let (code, _, stderr) = odetto_cli_in(&project, &["check"]);
+  assert_eq!(code, 1);
+  assert!(stderr.contains(" --> schema/orders.odet:4:15\n"), "{}", stderr);
+  assert_eq!(odetto_cli_in(&project, &["generate"]).0, 2);
+
+  std::fs::write(project.join("odetto.yml"), "lint: [everything]").unwrap();
+  let (code, _, stderr) = odetto_cli_in(&project, &["check"]);
+  assert_eq!(code, 2);
+  assert_eq!(stderr, "There is no lint rule called 'everything'\n");
+
+  std::fs::write(project.join("odetto.yml"), "schema: [schema/*.odet]").unwrap();
+  assert_eq!(odetto_cli_in(&project, &["generate"]).0, 2);
+
+  std::fs::remove_dir_all(&project).unwrap();
+}
+
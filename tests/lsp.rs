@@ -0,0 +1,242 @@
+#![cfg(feature = "lsp")]
+
+use std::fs;
+use std::thread;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use lsp_types::{notification::{self, Notification as _}, request::{self, Request as _}, Position, Uri};
+use serde_json::{json, Value};
+
+use odetto::{lsp, span::LineIndex};
+
+// a client talking to the server over an in-memory connection
+struct Client {
+  connection: Connection,
+  server: Option<thread::JoinHandle<()>>,
+  next_id: i32,
+}
+
+impl Client {
+  fn start() -> Client {
+    let (client, server) = Connection::memory();
+    let server = thread::spawn(move || lsp::run(&server).unwrap());
+    let mut client = Client { connection: client, server: Some(server), next_id: 0 };
+
+    let result = client.request(request::Initialize::METHOD, json!({ "capabilities": {} }));
+    assert_eq!(result["capabilities"]["definitionProvider"], json!(true));
+    client.notify(notification::Initialized::METHOD, json!({}));
+
+    client
+  }
+
+  fn request(&mut self, method: &str, params: Value) -> Value {
+    self.next_id += 1;
+    let id = RequestId::from(self.next_id);
+    self.connection.sender.send(Message::Request(Request::new(id.clone(), method.to_string(), params))).unwrap();
+
+    loop {
+      match self.connection.receiver.recv().unwrap() {
+        Message::Response(response) if response.id == id => {
+          assert!(response.error.is_none(), "{:?}", response.error);
+          return response.result.unwrap_or(Value::Null);
+        },
+        _ => {},
+      }
+    }
+  }
+
+  fn notify(&self, method: &str, params: Value) {
+    self.connection.sender.send(Message::Notification(Notification::new(method.to_string(), params))).unwrap();
+  }
+
+  // the next published diagnostics
+  fn diagnostics(&self) -> Value {
+    loop {
+      if let Message::Notification(n) = self.connection.receiver.recv().unwrap() {
+        if n.method == notification::PublishDiagnostics::METHOD {
+          return n.params;
+        }
+      }
+    }
+  }
+
+  fn open(&self, uri: &str, text: &str) -> Value {
+    self.notify(notification::DidOpenTextDocument::METHOD, json!({
+      "textDocument": { "uri": uri, "languageId": "odetto", "version": 1, "text": text },
+    }));
+    self.diagnostics()
+  }
+
+  fn at(&mut self, method: &str, uri: &str, position: Position) -> Value {
+    let mut params = json!({ "textDocument": { "uri": uri }, "position": position });
+    if method == request::References::METHOD {
+      params["context"] = json!({ "includeDeclaration": true });
+    }
+
+    self.request(method, params)
+  }
+}
+
+impl Drop for Client {
+  fn drop(&mut self) {
+    self.request(request::Shutdown::METHOD, Value::Null);
+    self.notify(notification::Exit::METHOD, Value::Null);
+    self.server.take().unwrap().join().unwrap();
+  }
+}
+
+const URI: &str = "file:///schema.odet";
+
+// a file next to the schema of the `shop` project, whose config declares `DateTime`
+#[cfg(feature = "config")]
+fn project_uri(name: &str) -> String {
+  let dir = std::env::current_dir().unwrap().join("tests/projects/shop/schema");
+  format!("file://{}/{}", dir.display(), name)
+}
+
+// position of the `nth` occurence of `needle`, moved `shift` characters to the right
+fn find(src: &str, needle: &str, nth: usize, shift: usize) -> Position {
+  let offset = src.match_indices(needle).nth(nth).unwrap().0 + shift;
+  lsp::position(&LineIndex::new(src), offset)
+}
+
+#[test]
+fn live_diagnostics() {
+  let mut client = Client::start();
+
+  let published = client.open(URI, "type Movie {\n    title: String\n    cast: [Actor]\n}\n");
+  assert_eq!(published["uri"], json!(URI));
+  assert_eq!(published["diagnostics"].as_array().unwrap().len(), 1);
+  assert_eq!(published["diagnostics"][0]["range"]["start"], json!({ "line": 2, "character": 11 }));
+  assert_eq!(published["diagnostics"][0]["severity"], json!(1));
+  assert_eq!(published["diagnostics"][0]["source"], json!("odetto"));
+
+  client.notify(notification::DidChangeTextDocument::METHOD, json!({
+    "textDocument": { "uri": URI, "version": 2 },
+    "contentChanges": [{ "text": "type Movie {\n    title: String\n    cast: [Actor]\n}\ntype Actor { name: String }\n" }],
+  }));
+  assert_eq!(client.diagnostics()["diagnostics"], json!([]));
+
+  client.notify(notification::DidCloseTextDocument::METHOD, json!({ "textDocument": { "uri": URI } }));
+  assert_eq!(client.diagnostics()["diagnostics"], json!([]));
+  assert_eq!(client.at(request::HoverRequest::METHOD, URI, Position::new(0, 6)), Value::Null);
+}
+
+#[test]
+#[cfg(feature = "config")]
+fn project_scalars() {
+  let uri = project_uri("draft.odet");
+  let src = "type Draft {\n    id: Int! @primary\n    saved_at: DateTime!\n}\n";
+  let client = Client::start();
+
+  assert_eq!(client.open(&uri, src)["diagnostics"], json!([]));
+  assert_eq!(client.open(URI, src)["diagnostics"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn definition_and_references() {
+  let src = fs::read_to_string("tests/odet-files/20-codegen.odet").unwrap();
+  let mut client = Client::start();
+  assert_eq!(client.open(URI, &src)["diagnostics"], json!([]));
+
+  // `Post` in `posts: [Post!]!`
+  let definition = client.at(request::GotoDefinition::METHOD, URI, find(&src, "Post!", 0, 2));
+  assert_eq!(definition["uri"], json!(URI));
+  assert_eq!(definition["range"]["start"], serde_json::to_value(find(&src, "type Post", 0, 5)).unwrap());
+
+  // the inverse field named by a relation
+  let definition = client.at(request::GotoDefinition::METHOD, URI, find(&src, "-> author", 0, 4));
+  assert_eq!(definition["range"]["start"], serde_json::to_value(find(&src, "author: User!", 0, 0)).unwrap());
+
+  let references = client.at(request::References::METHOD, URI, find(&src, "type User", 0, 6));
+  let starts: Vec<Value> = references.as_array().unwrap().iter().map(|l| l["range"]["start"].clone()).collect();
+  assert_eq!(starts, vec![
+    serde_json::to_value(find(&src, "User @db", 0, 0)).unwrap(),
+    serde_json::to_value(find(&src, "User! -> posts", 0, 0)).unwrap(),
+    serde_json::to_value(find(&src, "User!\n", 0, 0)).unwrap(),
+    serde_json::to_value(find(&src, "[User]", 0, 1)).unwrap(),
+  ]);
+
+  let references = client.at(request::References::METHOD, URI, find(&src, "parent: Group", 0, 0));
+  assert_eq!(references.as_array().unwrap().len(), 2);
+
+  assert_eq!(client.at(request::GotoDefinition::METHOD, URI, find(&src, "String!", 0, 2)), Value::Null);
+}
+
+#[test]
+fn hover() {
+  let src = fs::read_to_string("tests/odet-files/20-codegen.odet").unwrap();
+  let mut client = Client::start();
+  client.open(URI, &src);
+
+  let hover = client.at(request::HoverRequest::METHOD, URI, find(&src, "User!", 0, 1));
+  assert_eq!(hover["contents"]["kind"], json!("markdown"));
+  assert_eq!(hover["contents"]["value"], json!("```odetto\ntype User @db(name: \"users\")\n```\n\npeople using the site"));
+  assert_eq!(hover["range"]["start"], serde_json::to_value(find(&src, "User!", 0, 0)).unwrap());
+
+  let hover = client.at(request::HoverRequest::METHOD, URI, find(&src, "Role!", 0, 0));
+  assert_eq!(hover["contents"]["value"], json!("```odetto\nenum Role { ADMIN MEMBER }\n```\n\nwhat a user is allowed to do"));
+
+  let hover = client.at(request::HoverRequest::METHOD, URI, find(&src, "name: String!", 0, 2));
+  assert_eq!(hover["contents"]["value"], json!("```odetto\nUser.name: String!\n```\n\nshown on every post"));
+}
+
+#[test]
+fn completion() {
+  let mut client = Client::start();
+  let src = "\"\"\"a film\"\"\"\ntype Movie {\n    genre: G\n    tags: [\n}\nenum Genre { A }\ntype Tag @db(name: \"tags\") { label: String }\n";
+  client.open(URI, src);
+
+  let items = client.at(request::Completion::METHOD, URI, find(src, "G\n", 0, 1));
+  let labels: Vec<&str> = items.as_array().unwrap().iter().map(|i| i["label"].as_str().unwrap()).collect();
+  assert_eq!(labels, vec!["Int", "String", "Movie", "Genre", "Tag"]);
+  assert_eq!(items[2]["documentation"], json!("a film"));
+  assert_eq!(items[3]["kind"], json!(13));
+
+  let items = client.at(request::Completion::METHOD, URI, find(src, "[\n", 0, 1));
+  assert_eq!(items.as_array().unwrap().len(), 5);
+
+  assert_eq!(client.at(request::Completion::METHOD, URI, find(src, "name: \"tags\"", 0, 6)), json!([]));
+  assert_eq!(client.at(request::Completion::METHOD, URI, find(src, "genre", 0, 2)), json!([]));
+
+  // the scalars of the project config next to the file
+  #[cfg(feature = "config")]
+  {
+    let uri = project_uri("draft.odet");
+    client.open(&uri, src);
+    let items = client.at(request::Completion::METHOD, &uri, find(src, "G\n", 0, 1));
+    let labels: Vec<&str> = items.as_array().unwrap().iter().map(|i| i["label"].as_str().unwrap()).collect();
+    assert_eq!(labels, vec!["Int", "String", "DateTime", "Movie", "Genre", "Tag"]);
+    assert_eq!(items[2]["kind"], items[0]["kind"]);
+  }
+}
+
+#[test]
+fn document_symbols() {
+  let src = "type Movie {\n    title: String!\n    genre: Genre\n}\n\nenum Genre { COMEDY DRAMA }\n";
+  let mut client = Client::start();
+  client.open(URI, src);
+
+  let symbols = client.request(request::DocumentSymbolRequest::METHOD, json!({ "textDocument": { "uri": URI } }));
+  assert_eq!(symbols[0]["name"], json!("Movie"));
+  assert_eq!(symbols[0]["kind"], json!(23));
+  assert_eq!(symbols[0]["range"], json!({ "start": { "line": 0, "character": 0 }, "end": { "line": 3, "character": 1 } }));
+  assert_eq!(symbols[0]["selectionRange"]["start"], json!({ "line": 0, "character": 5 }));
+  assert_eq!(symbols[0]["children"][0]["name"], json!("title"));
+  assert_eq!(symbols[0]["children"][0]["detail"], json!("String!"));
+  assert_eq!(symbols[0]["children"][1]["kind"], json!(8));
+  assert_eq!(symbols[1]["name"], json!("Genre"));
+  assert_eq!(symbols[1]["children"][1]["name"], json!("DRAMA"));
+}
+
+#[test]
+fn positions_count_utf16() {
+  let src = "# 😀 é\ntype A { b: Int }";
+  let index = LineIndex::new(src);
+
+  assert_eq!(lsp::position(&index, 7), Position::new(0, 5));
+  assert_eq!(lsp::offset(&index, Position::new(0, 5)), Some(7));
+  assert_eq!(lsp::offset(&index, Position::new(1, 5)), Some(src.find("A {").unwrap()));
+  assert_eq!(lsp::offset(&index, Position::new(5, 0)), None);
+  assert!("file:///a.odet".parse::<Uri>().is_ok());
+}
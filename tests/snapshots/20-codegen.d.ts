@@ -0,0 +1,39 @@
+/** people using the site */
+export interface User {
+    id: number;
+    /** shown on every post */
+    name: string;
+    email: string;
+    nicknames?: string[];
+    posts: Post[];
+    profile?: Profile;
+    groups?: (Group | null)[];
+    role: Role;
+}
+
+export interface Post {
+    id: number;
+    title: string;
+    author: User;
+    tags?: (Tag | null)[];
+    views: number;
+}
+
+export interface Profile {
+    user: User;
+    bio?: string;
+}
+
+export interface Group {
+    name: string;
+    members?: (User | null)[];
+    parent?: Group;
+    children?: (Group | null)[];
+}
+
+export interface Tag {
+    label: string;
+}
+
+/** what a user is allowed to do */
+export type Role = "ADMIN" | "MEMBER";
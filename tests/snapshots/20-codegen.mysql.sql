@@ -0,0 +1,53 @@
+CREATE TABLE `users` (
+    `id` INT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    `name` TEXT NOT NULL COMMENT 'shown on every post',
+    `email` VARCHAR(255) NOT NULL UNIQUE,
+    `nicknames` JSON DEFAULT ('[]'),
+    `role` ENUM('ADMIN', 'MEMBER') NOT NULL DEFAULT 'MEMBER'
+) COMMENT = 'people using the site';
+
+CREATE TABLE `post` (
+    `id` INT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    `title` VARCHAR(120) NOT NULL,
+    `author_id` INT NOT NULL,
+    `views` INT NOT NULL DEFAULT 0
+);
+
+CREATE TABLE `profile` (
+    `id` INT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    `user_id` INT NOT NULL UNIQUE,
+    `bio` TEXT
+);
+
+CREATE TABLE `group` (
+    `id` INT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    `name` TEXT NOT NULL,
+    `parent_id` INT
+);
+
+CREATE TABLE `tag` (
+    `label` VARCHAR(255) PRIMARY KEY
+);
+
+CREATE TABLE `users_groups` (
+    `user_id` INT NOT NULL,
+    `group_id` INT NOT NULL,
+    PRIMARY KEY (`user_id`, `group_id`)
+);
+
+CREATE TABLE `post_tags` (
+    `post_id` INT NOT NULL,
+    `tag_id` VARCHAR(255) NOT NULL,
+    PRIMARY KEY (`post_id`, `tag_id`)
+);
+
+ALTER TABLE `post` ADD CONSTRAINT `post_author_id_fkey` FOREIGN KEY (`author_id`) REFERENCES `users` (`id`);
+ALTER TABLE `profile` ADD CONSTRAINT `profile_user_id_fkey` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`);
+ALTER TABLE `group` ADD CONSTRAINT `group_parent_id_fkey` FOREIGN KEY (`parent_id`) REFERENCES `group` (`id`);
+ALTER TABLE `users_groups` ADD CONSTRAINT `users_groups_user_id_fkey` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE;
+ALTER TABLE `users_groups` ADD CONSTRAINT `users_groups_group_id_fkey` FOREIGN KEY (`group_id`) REFERENCES `group` (`id`) ON DELETE CASCADE;
+ALTER TABLE `post_tags` ADD CONSTRAINT `post_tags_post_id_fkey` FOREIGN KEY (`post_id`) REFERENCES `post` (`id`) ON DELETE CASCADE;
+ALTER TABLE `post_tags` ADD CONSTRAINT `post_tags_tag_id_fkey` FOREIGN KEY (`tag_id`) REFERENCES `tag` (`label`) ON DELETE CASCADE;
+
+CREATE INDEX `post_author_id_idx` ON `post` (`author_id`);
+CREATE INDEX `post_author_id_views_idx` ON `post` (`author_id`, `views`);
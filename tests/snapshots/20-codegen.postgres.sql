@@ -0,0 +1,58 @@
+CREATE TYPE "role" AS ENUM ('ADMIN', 'MEMBER');
+COMMENT ON TYPE "role" IS 'what a user is allowed to do';
+
+CREATE TABLE "users" (
+    "id" SERIAL PRIMARY KEY,
+    "name" TEXT NOT NULL,
+    "email" TEXT NOT NULL UNIQUE,
+    "nicknames" TEXT[] DEFAULT '{}',
+    "role" "role" NOT NULL DEFAULT 'MEMBER'
+);
+COMMENT ON TABLE "users" IS 'people using the site';
+COMMENT ON COLUMN "users"."name" IS 'shown on every post';
+
+CREATE TABLE "post" (
+    "id" SERIAL PRIMARY KEY,
+    "title" VARCHAR(120) NOT NULL,
+    "author_id" INTEGER NOT NULL,
+    "views" INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE "profile" (
+    "id" SERIAL PRIMARY KEY,
+    "user_id" INTEGER NOT NULL UNIQUE,
+    "bio" TEXT
+);
+
+CREATE TABLE "group" (
+    "id" SERIAL PRIMARY KEY,
+    "name" TEXT NOT NULL,
+    "parent_id" INTEGER
+);
+
+CREATE TABLE "tag" (
+    "label" TEXT PRIMARY KEY
+);
+
+CREATE TABLE "users_groups" (
+    "user_id" INTEGER NOT NULL,
+    "group_id" INTEGER NOT NULL,
+    PRIMARY KEY ("user_id", "group_id")
+);
+
+CREATE TABLE "post_tags" (
+    "post_id" INTEGER NOT NULL,
+    "tag_id" TEXT NOT NULL,
+    PRIMARY KEY ("post_id", "tag_id")
+);
+
+ALTER TABLE "post" ADD CONSTRAINT "post_author_id_fkey" FOREIGN KEY ("author_id") REFERENCES "users" ("id");
+ALTER TABLE "profile" ADD CONSTRAINT "profile_user_id_fkey" FOREIGN KEY ("user_id") REFERENCES "users" ("id");
+ALTER TABLE "group" ADD CONSTRAINT "group_parent_id_fkey" FOREIGN KEY ("parent_id") REFERENCES "group" ("id");
+ALTER TABLE "users_groups" ADD CONSTRAINT "users_groups_user_id_fkey" FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE CASCADE;
+ALTER TABLE "users_groups" ADD CONSTRAINT "users_groups_group_id_fkey" FOREIGN KEY ("group_id") REFERENCES "group" ("id") ON DELETE CASCADE;
+ALTER TABLE "post_tags" ADD CONSTRAINT "post_tags_post_id_fkey" FOREIGN KEY ("post_id") REFERENCES "post" ("id") ON DELETE CASCADE;
+ALTER TABLE "post_tags" ADD CONSTRAINT "post_tags_tag_id_fkey" FOREIGN KEY ("tag_id") REFERENCES "tag" ("label") ON DELETE CASCADE;
+
+CREATE INDEX "post_author_id_idx" ON "post" ("author_id");
+CREATE INDEX "post_author_id_views_idx" ON "post" ("author_id", "views");
@@ -0,0 +1,50 @@
+/// people using the site
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct User {
+    pub id: i64,
+    /// shown on every post
+    pub name: String,
+    pub email: String,
+    pub nicknames: Option<Vec<String>>,
+    pub posts: Vec<Post>,
+    pub profile: Option<Box<Profile>>,
+    pub groups: Option<Vec<Option<Group>>>,
+    pub role: Role,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Post {
+    pub id: i64,
+    pub title: String,
+    pub author: User,
+    pub tags: Option<Vec<Option<Tag>>>,
+    pub views: i64,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Profile {
+    pub user: Box<User>,
+    pub bio: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Group {
+    pub name: String,
+    pub members: Option<Vec<Option<User>>>,
+    pub parent: Option<Box<Group>>,
+    pub children: Option<Vec<Option<Group>>>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Tag {
+    pub label: String,
+}
+
+/// what a user is allowed to do
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum Role {
+    #[serde(rename = "ADMIN")]
+    Admin,
+    #[serde(rename = "MEMBER")]
+    Member,
+}
@@ -0,0 +1,54 @@
+-- people using the site
+CREATE TABLE "users" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- shown on every post
+    "name" TEXT NOT NULL,
+    "email" TEXT NOT NULL UNIQUE,
+    "nicknames" TEXT DEFAULT '[]',
+    "role" TEXT NOT NULL DEFAULT 'MEMBER' CHECK ("role" IN ('ADMIN', 'MEMBER'))
+);
+
+CREATE TABLE "post" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "title" TEXT NOT NULL CHECK (length("title") <= 120),
+    "author_id" INTEGER NOT NULL,
+    "views" INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY ("author_id") REFERENCES "users" ("id")
+);
+
+CREATE TABLE "profile" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "user_id" INTEGER NOT NULL UNIQUE,
+    "bio" TEXT,
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id")
+);
+
+CREATE TABLE "group" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "name" TEXT NOT NULL,
+    "parent_id" INTEGER,
+    FOREIGN KEY ("parent_id") REFERENCES "group" ("id")
+);
+
+CREATE TABLE "tag" (
+    "label" TEXT PRIMARY KEY
+);
+
+CREATE TABLE "users_groups" (
+    "user_id" INTEGER NOT NULL,
+    "group_id" INTEGER NOT NULL,
+    PRIMARY KEY ("user_id", "group_id"),
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE CASCADE,
+    FOREIGN KEY ("group_id") REFERENCES "group" ("id") ON DELETE CASCADE
+);
+
+CREATE TABLE "post_tags" (
+    "post_id" INTEGER NOT NULL,
+    "tag_id" TEXT NOT NULL,
+    PRIMARY KEY ("post_id", "tag_id"),
+    FOREIGN KEY ("post_id") REFERENCES "post" ("id") ON DELETE CASCADE,
+    FOREIGN KEY ("tag_id") REFERENCES "tag" ("label") ON DELETE CASCADE
+);
+
+CREATE INDEX "post_author_id_idx" ON "post" ("author_id");
+CREATE INDEX "post_author_id_views_idx" ON "post" ("author_id", "views");
@@ -0,0 +1,42 @@
+CREATE TYPE "genre" AS ENUM ('COMEDY', 'DRAMA');
+
+CREATE TABLE "movie" (
+    "slug" TEXT PRIMARY KEY,
+    "title" TEXT
+);
+
+CREATE TABLE "review" (
+    "code" SERIAL PRIMARY KEY,
+    "movie_id" TEXT NOT NULL
+);
+
+CREATE TABLE "tag" (
+    "label" TEXT PRIMARY KEY
+);
+
+CREATE TABLE "shelf" (
+    "id" "genre" PRIMARY KEY,
+    "label" TEXT
+);
+
+CREATE TABLE "pick" (
+    "genre" "genre" PRIMARY KEY,
+    "movie_id" TEXT NOT NULL
+);
+
+CREATE TABLE "playlist" (
+    "_id" SERIAL PRIMARY KEY,
+    "id" INTEGER[],
+    "name" TEXT
+);
+
+CREATE TABLE "movie_tags" (
+    "movie_id" TEXT NOT NULL,
+    "tag_id" TEXT NOT NULL,
+    PRIMARY KEY ("movie_id", "tag_id")
+);
+
+ALTER TABLE "review" ADD CONSTRAINT "review_movie_id_fkey" FOREIGN KEY ("movie_id") REFERENCES "movie" ("slug");
+ALTER TABLE "pick" ADD CONSTRAINT "pick_movie_id_fkey" FOREIGN KEY ("movie_id") REFERENCES "movie" ("slug");
+ALTER TABLE "movie_tags" ADD CONSTRAINT "movie_tags_movie_id_fkey" FOREIGN KEY ("movie_id") REFERENCES "movie" ("slug") ON DELETE CASCADE;
+ALTER TABLE "movie_tags" ADD CONSTRAINT "movie_tags_tag_id_fkey" FOREIGN KEY ("tag_id") REFERENCES "tag" ("label") ON DELETE CASCADE;
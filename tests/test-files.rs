@@ -1,7 +1,7 @@
 use std::fs;
 use std::time::{Instant};
 
-use odetto::{lexer, parser, ast, _helpers::ParseError};
+use odetto::{lexer, parser, ast, ast::{AnnotationValue, Cardinality, FieldTypeType}, _helpers::{ParseError, MigrationError, ImportError, RenameError, GenerateError, GenerateResult}, diagnostics::Diagnostic, migration, diff::{self, Change, FieldChange}, compat::{self, Compatibility, Consumer}, codegen, import, format, cst, refactor, span::LineIndex};
 
 #[test]
 fn happy_parser() {
@@ -38,6 +38,17 @@ fn duplicate_field_parser() {
   println!("{}", result.unwrap_err());
 }
 
+#[test]
+fn duplicate_field_location() {
+  match test_file("tests/odet-files/6-duplicate-field-name-parser.odet") {
+    Err(ParseError::DuplicateFieldIdentifierError(info)) => {
+      assert_eq!(info.line_col.to_string(), "5:5");
+      assert_eq!(info.span.len(), "description".len());
+    },
+    other => panic!("expected duplicate field error, got {:?}", other),
+  }
+}
+
 #[test]
 fn missing_type_parser() {
   let result = test_file("tests/odet-files/7-missing-type-parser.odet");
@@ -45,6 +56,61 @@ fn missing_type_parser() {
   println!("{}", result.unwrap_err());
 }
 
+#[test]
+fn source_spans() {
+  let contents = fs::read_to_string("tests/odet-files/9-annotation-parser.odet").unwrap();
+  let root = test_file("tests/odet-files/9-annotation-parser.odet").unwrap();
+  let movie = &root.types[0];
+
+  assert_eq!(&contents[movie.name_span.start..movie.name_span.end], "Movie");
+  assert!(contents[movie.span.start..movie.span.end].starts_with("type Movie {"));
+  assert!(contents[movie.span.start..movie.span.end].ends_with('}'));
+
+  let comments = &movie.fields[3];
+  assert_eq!(&contents[comments.span.start..comments.span.end], "comments: [Comment]");
+  assert_eq!(&contents[comments.field_type.span().start..comments.field_type.span().end], "Comment");
+}
+
+#[test]
+fn missing_type_diagnostic() {
+  let file_name = "tests/odet-files/7-missing-type-parser.odet";
+  let contents = fs::read_to_string(file_name).unwrap();
+  let tokens = lexer::Lexer::new(contents.as_str()).run();
+  let error = parser::Parser::new(&tokens).parse().unwrap_err();
+  let rendered = Diagnostic::from_parse_error(&error, tokens.line_index()).render(file_name, tokens.line_index());
+  println!("{}", rendered);
+
+  assert!(rendered.starts_with("error[E0011]: cannot find type `Comment`"));
+  assert!(rendered.contains("--> tests/odet-files/7-missing-type-parser.odet:5:16"));
+  assert!(rendered.contains("5 |     comments: [Comment]"));
+}
+
+#[test]
+fn recovery_parser() {
+  let contents = fs::read_to_string("tests/odet-files/10-recovery-parser.odet").unwrap();
+  let tokens = lexer::Lexer::new(contents.as_str()).run();
+  let (root, diagnostics) = parser::Parser::new(&tokens).parse_recovering();
+
+  for d in diagnostics.iter() {
+    println!("{}", d.render("tests/odet-files/10-recovery-parser.odet", tokens.line_index()));
+  }
+
+  let codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+  assert_eq!(codes, vec!["E0005", "E0003", "E0011", "E0004", "E0005", "E0011"]);
+
+  let names: Vec<&str> = root.types.iter().map(|t| t.name.as_str()).collect();
+  assert_eq!(names, vec!["Movie", "Comment", "Review", "Tag"]);
+  assert_eq!(root.types[1].fields.len(), 3);
+  assert_eq!(root.types[2].fields.len(), 1);
+  assert_eq!(root.types[3].fields.len(), 3);
+}
+
+#[test]
+fn recovery_parser_first_error() {
+  let result = test_file("tests/odet-files/10-recovery-parser.odet");
+  assert!(matches!(result, Err(ParseError::MissingColonError(_))), "errored correctly!");
+}
+
 #[test]
 fn comments_parser() {
   let result = test_file("tests/odet-files/8-comments-parser.odet");
@@ -59,6 +125,916 @@ fn annotation_parser() {
   println!("{}", result.unwrap());
 }
 
+#[test]
+fn enum_parser() {
+  let result = test_file("tests/odet-files/11-enum-parser.odet");
+  assert!(result.is_ok(), "all good!");
+  let root = result.unwrap();
+  println!("{}", root);
+
+  let status = root.enum_def("Status").unwrap();
+  let variants: Vec<&str> = status.variants.iter().map(|v| v.name.as_str()).collect();
+  assert_eq!(variants, vec!["DRAFT", "PUBLISHED", "ARCHIVED"]);
+  assert_eq!(status.doc.as_deref(), Some("publication state of a post"));
+  assert_eq!(status.variants[1].doc.as_deref(), Some("visible to everyone"));
+  assert_eq!(root.enum_def("Role").unwrap().variants.len(), 3);
+  assert_eq!(root.model("Post").unwrap().fields[1].field_type.name(), "Status");
+}
+
+#[test]
+fn enum_errors_parser() {
+  let contents = fs::read_to_string("tests/odet-files/12-enum-errors-parser.odet").unwrap();
+  let tokens = lexer::Lexer::new(contents.as_str()).run();
+  let (root, diagnostics) = parser::Parser::new(&tokens).parse_recovering();
+
+  let codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+  assert_eq!(codes, vec!["E0014", "E0013", "E0004"]);
+  assert_eq!(root.enums.len(), 2);
+  assert!(root.types.is_empty());
+}
+
+#[test]
+fn annotations_parser() {
+  let result = test_file("tests/odet-files/13-annotations-parser.odet");
+  assert!(result.is_ok(), "all good!");
+  let root = result.unwrap();
+  println!("{}", root);
+
+  let movie = root.model("Movie").unwrap();
+  assert_eq!(movie.doc.as_deref(), Some("a movie"));
+  assert_eq!(movie.annotations.len(), 2);
+  assert_eq!(movie.annotations[0].arg("name"), Some(&AnnotationValue::String(String::from("movies"))));
+  assert_eq!(movie.annotations[1].to_string(), r#"@index(fields: ["name", "rating"], unique: false)"#);
+
+  let fields = &movie.fields;
+  assert_eq!(fields[0].annotations[0].name, "primary");
+  assert!(fields[0].required);
+  assert_eq!(fields[1].doc.as_deref(), Some("display name"));
+  assert_eq!(fields[1].annotations[0].name, "unique");
+  assert_eq!(fields[2].annotations[0].arg("value"), Some(&AnnotationValue::Int(0)));
+  assert_eq!(fields[2].annotations[1].arg("min"), Some(&AnnotationValue::Int(-10)));
+  assert_eq!(fields[2].annotations[1].arg("max"), Some(&AnnotationValue::Float(10.5)));
+  assert_eq!(fields[3].annotations[0].arg("value"), Some(&AnnotationValue::Identifier(String::from("DRAFT"))));
+  assert_eq!(root.enum_def("Status").unwrap().annotations[0].to_string(), r#"@db(type: "movie_status")"#);
+}
+
+#[test]
+fn annotation_errors_parser() {
+  let contents = fs::read_to_string("tests/odet-files/14-annotation-errors-parser.odet").unwrap();
+  let tokens = lexer::Lexer::new(contents.as_str()).run();
+  let (_, diagnostics) = parser::Parser::new(&tokens).parse_recovering();
+
+  for d in diagnostics.iter() {
+    println!("{}", d.render("tests/odet-files/14-annotation-errors-parser.odet", tokens.line_index()));
+  }
+
+  let codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+  assert_eq!(codes, vec!["E0015", "E0015"]);
+}
+
+#[test]
+fn relation_parser() {
+  let result = test_file("tests/odet-files/15-relation-parser.odet");
+  assert!(result.is_ok(), "all good!");
+  let root = result.unwrap();
+  println!("{}", root);
+
+  let cardinality = |model: &str, field: &str| {
+    let f = root.model(model).unwrap().fields.iter().find(|f| f.name == field).unwrap();
+    f.relation.as_ref().unwrap().cardinality
+  };
+
+  assert_eq!(cardinality("User", "posts"), Some(Cardinality::OneToMany));
+  assert_eq!(cardinality("Post", "author"), Some(Cardinality::ManyToOne));
+  assert_eq!(cardinality("User", "profile"), Some(Cardinality::OneToOne));
+  assert_eq!(cardinality("User", "groups"), Some(Cardinality::ManyToMany));
+  assert_eq!(cardinality("Group", "parent"), Some(Cardinality::ManyToOne));
+
+  let author = &root.model("Post").unwrap().fields[1];
+  assert_eq!(author.annotations[0].name, "index");
+  assert_eq!(root.relation_inverse(author).unwrap().name, "posts");
+  assert!(root.model("Profile").unwrap().fields[0].relation.is_none());
+}
+
+#[test]
+fn relation_errors_parser() {
+  let contents = fs::read_to_string("tests/odet-files/16-relation-errors-parser.odet").unwrap();
+  let tokens = lexer::Lexer::new(contents.as_str()).run();
+  let (_, diagnostics) = parser::Parser::new(&tokens).parse_recovering();
+
+  for d in diagnostics.iter() {
+    println!("{}", d.render("tests/odet-files/16-relation-errors-parser.odet", tokens.line_index()));
+  }
+
+  let codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+  assert_eq!(codes, vec!["E0016", "E0017", "E0017", "E0017", "E0017", "E0016", "E0017"]);
+}
+
+#[test]
+fn migration_parser() {
+  let root = test_file("tests/odet-files/17-migration-base.odet").unwrap();
+  let contents = fs::read_to_string("tests/odet-files/18-migration.odet").unwrap();
+  let tokens = lexer::Lexer::new(contents.as_str()).run();
+  let migration = parser::Parser::new(&tokens).parse_migration().unwrap();
+  println!("{:#?}", migration.transitions);
+  assert_eq!(migration.transitions.len(), 4);
+
+  let result = migration::apply(&root, &migration).unwrap();
+  println!("{}", result);
+
+  let names: Vec<&str> = result.types.iter().map(|t| t.name.as_str()).collect();
+  assert_eq!(names, vec!["Movie", "Review", "Comment"]);
+
+  let movie = result.model("Movie").unwrap();
+  let fields: Vec<&str> = movie.fields.iter().map(|f| f.name.as_str()).collect();
+  assert_eq!(fields, vec!["title", "rating", "critiques", "comments", "genre"]);
+  assert_eq!(movie.annotations[0].name, "db");
+  assert!(movie.fields[0].annotations.is_empty());
+  assert!(movie.fields[1].required);
+  assert_eq!(movie.fields[3].type_type, FieldTypeType::Array);
+  assert_eq!(movie.fields[3].doc.as_deref(), Some("all comments on the movie"));
+
+  let film = &result.model("Review").unwrap().fields[0];
+  assert_eq!(film.field_type.name(), "Movie");
+  assert_eq!(film.relation.as_ref().unwrap().inverse, "critiques");
+  assert_eq!(film.relation.as_ref().unwrap().cardinality, Some(Cardinality::ManyToOne));
+  assert_eq!(result.enum_def("Genre").unwrap().variants.len(), 3);
+}
+
+#[test]
+fn migration_errors() {
+  let root = test_file("tests/odet-files/17-migration-base.odet").unwrap();
+  let apply = |src: &str| {
+    let tokens = lexer::Lexer::new(src).run();
+    let migration = parser::Parser::new(&tokens).parse_migration().unwrap();
+    migration::apply(&root, &migration)
+  };
+
+  assert!(matches!(apply("type Film { -missing }"), Err(MigrationError::UnknownFieldError(_, _, _))));
+  assert!(matches!(apply("type Film { +rating: Int }"), Err(MigrationError::DuplicateFieldError(_, _, _))));
+  assert!(matches!(apply("+type Review { id: Int }"), Err(MigrationError::DuplicateModelError(_, _))));
+  assert!(matches!(apply("-type Review"), Err(MigrationError::MissingReferenceError(_, _, _))));
+  assert!(matches!(apply("type Review { -film }"), Err(MigrationError::MissingInverseFieldError(_, _, _))));
+  // the inverse still exists but no longer points back at `Review`
+  assert!(matches!(apply("type Film { reviews: [Legacy] }"), Err(MigrationError::InvalidRelationError(_, _, _))));
+  assert!(matches!(apply("type Film { reviews: [Review] -> stars }"), Err(MigrationError::InvalidRelationError(_, _, _))));
+  assert!(matches!(apply("-enum Mood"), Err(MigrationError::UnknownEnumError(_, _))));
+}
+
+#[test]
+fn diff_schemas() {
+  let old = test_file("tests/odet-files/17-migration-base.odet").unwrap();
+  let new = test_file("tests/odet-files/19-diff-new.odet").unwrap();
+  let schema_diff = diff::diff(&old, &new);
+  println!("{:#?}", schema_diff.changes);
+
+  assert!(matches!(&schema_diff.changes[0], Change::ModelRenamed { from, to } if from == "Legacy" && to == "Archive"));
+  assert!(schema_diff.changes.iter().any(|c| matches!(c, Change::ModelAdded(m) if m.name == "Movie")));
+  assert!(schema_diff.changes.iter().any(|c| matches!(c, Change::ModelRemoved(m) if m.name == "Film")));
+  assert!(schema_diff.changes.iter().any(|c| matches!(c, Change::ModelDocChanged { model, .. } if model == "Review")));
+  assert!(schema_diff.changes.iter().any(|c| matches!(c, Change::EnumAdded(e) if e.name == "Mood")));
+  assert!(schema_diff.changes.iter().any(|c| matches!(c, Change::EnumChanged { added_variants, .. } if added_variants == &vec![String::from("HORROR")])));
+
+  let stars = schema_diff.changes.iter().find_map(|c| match c {
+    Change::FieldChanged { to, changes, .. } if to.name == "stars" => Some(changes.clone()),
+    _ => None,
+  }).unwrap();
+  assert_eq!(stars, vec![FieldChange::Array { from: FieldTypeType::Basic, to: FieldTypeType::RequiredArray }]);
+
+  let film = schema_diff.changes.iter().find_map(|c| match c {
+    Change::FieldChanged { to, changes, .. } if to.name == "film" => Some(changes.clone()),
+    _ => None,
+  }).unwrap();
+  assert_eq!(film.len(), 2);
+}
+
+#[test]
+fn compat_checker() {
+  let old = test_file("tests/odet-files/17-migration-base.odet").unwrap();
+  let new = test_file("tests/odet-files/19-diff-new.odet").unwrap();
+  let report = compat::classify(&old, &diff::diff(&old, &new), Consumer::All);
+  for finding in report.findings.iter() {
+    println!("{}", finding);
+  }
+
+  assert!(report.is_breaking());
+  assert!(report.findings.iter().any(|f| f.compatibility == Compatibility::Breaking && f.message == "model `Film` was removed"));
+  assert!(report.findings.iter().any(|f| f.compatibility == Compatibility::Risky && f.message == "model `Legacy` was renamed to `Archive`"));
+  assert!(report.findings.iter().any(|f| f.compatibility == Compatibility::Safe && f.message == "enum `Mood` was added"));
+
+  let stars = report.findings.iter().find(|f| f.field.as_deref() == Some("stars")).unwrap();
+  assert_eq!(stars.compatibility, Compatibility::Breaking);
+
+  let unchanged = compat::classify(&old, &diff::diff(&old, &old), Consumer::All);
+  assert!(unchanged.findings.is_empty());
+  assert_eq!(unchanged.worst(), Compatibility::Safe);
+}
+
+#[test]
+fn diff_to_migration_round_trip() {
+  let old = test_file("tests/odet-files/17-migration-base.odet").unwrap();
+  let new = test_file("tests/odet-files/19-diff-new.odet").unwrap();
+  let text = diff::diff(&old, &new).to_migration();
+  println!("{}", text);
+
+  let tokens = lexer::Lexer::new(text.as_str()).run();
+  let migration = parser::Parser::new(&tokens).parse_migration().unwrap();
+  let applied = migration::apply(&old, &migration).unwrap();
+
+  let remaining = diff::diff(&applied, &new);
+  assert!(remaining.is_empty(), "{:#?}", remaining.changes);
+  assert!(diff::diff(&new, &new).to_migration().is_empty());
+}
+
+#[test]
+fn diff_to_migration_clears_model_doc_and_annotations() {
+  let round_trip = |old: &str, new: &str| {
+    let (old, new) = (test_source(old), test_source(new));
+    let text = diff::diff(&old, &new).to_migration();
+    let tokens = lexer::Lexer::new(text.as_str()).run();
+    let migration = parser::Parser::new(&tokens).parse_migration().unwrap();
+    let applied = migration::apply(&old, &migration).unwrap();
+
+    let remaining = diff::diff(&applied, &new);
+    assert!(remaining.is_empty(), "{}\n{:#?}", text, remaining.changes);
+    text
+  };
+
+  let text = round_trip("\"\"\"old\"\"\"\ntype M @db(name: \"m\") { a: Int }", "type M { a: Int }");
+  assert_eq!(text, "type M @clear(doc: true, annotations: true) {\n}\n");
+  round_trip("type M @db(name: \"m\") { a: Int }", "\"\"\"new\"\"\"\ntype M @index(fields: [\"a\"]) { a: Int }");
+  round_trip("\"\"\"old\"\"\"\ntype M { a: Int }", "type M @db(name: \"m\") { a: Int }");
+
+  let tokens = lexer::Lexer::new("type M @clear(doc: yes) { }").run();
+  assert!(matches!(parser::Parser::new(&tokens).parse_migration(), Err(ParseError::GenericAnnotationError(..))));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn diff_serializes() {
+  let old = test_file("tests/odet-files/17-migration-base.odet").unwrap();
+  let new = test_file("tests/odet-files/19-diff-new.odet").unwrap();
+  let json = serde_json::to_string(&diff::diff(&old, &new)).unwrap();
+  assert!(json.contains(r#""ModelRenamed":{"from":"Legacy","to":"Archive"}"#));
+}
+
+#[test]
+fn postgres_codegen() {
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+  let sql = codegen::sql::postgres::generate(&root);
+  println!("{}", sql);
+
+  assert_eq!(sql, fs::read_to_string("tests/snapshots/20-codegen.postgres.sql").unwrap());
+  assert_eq!(sql, codegen::sql::postgres::generate(&root));
+}
+
+#[test]
+fn postgres_primary_keys() {
+  let root = test_file("tests/odet-files/23-primary-key.odet").unwrap();
+  let sql = codegen::sql::postgres::generate(&root);
+  println!("{}", sql);
+
+  assert_eq!(sql, fs::read_to_string("tests/snapshots/23-primary-key.postgres.sql").unwrap());
+}
+
+#[test]
+fn sqlite_codegen() {
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+  let sql = codegen::sql::sqlite::generate(&root);
+  println!("{}", sql);
+
+  assert_eq!(sql, fs::read_to_string("tests/snapshots/20-codegen.sqlite.sql").unwrap());
+
+  let db = rusqlite::Connection::open_in_memory().unwrap();
+  db.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+  db.execute_batch(&sql).unwrap();
+
+  db.execute("INSERT INTO users (name, email) VALUES ('ann', 'ann@example.com')", []).unwrap();
+  let (role, nicknames): (String, String) = db.query_row("SELECT role, nicknames FROM users", [], |r| Ok((r.get(0)?, r.get(1)?))).unwrap();
+  assert_eq!((role.as_str(), nicknames.as_str()), ("MEMBER", "[]"));
+
+  db.execute("INSERT INTO post (title, author_id) VALUES ('first', 1)", []).unwrap();
+  db.execute("INSERT INTO tag (label) VALUES ('rust')", []).unwrap();
+  db.execute("INSERT INTO post_tags (post_id, tag_id) VALUES (1, 'rust')", []).unwrap();
+  assert!(db.execute("INSERT INTO post (title, author_id) VALUES ('orphan', 99)", []).is_err(), "foreign key");
+  assert!(db.execute("INSERT INTO users (name, email, role) VALUES ('bob', 'bob@example.com', 'GUEST')", []).is_err(), "enum check");
+  assert!(db.execute("INSERT INTO users (name, email) VALUES ('ann', 'ann@example.com')", []).is_err(), "unique email");
+  assert!(db.execute(&format!("INSERT INTO post (title, author_id) VALUES ('{}', 1)", "a".repeat(121)), []).is_err(), "title length");
+
+  db.execute("DELETE FROM post", []).unwrap();
+  let tags: i64 = db.query_row("SELECT count(*) FROM post_tags", [], |r| r.get(0)).unwrap();
+  assert_eq!(tags, 0);
+}
+
+#[test]
+fn mysql_codegen() {
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+  let sql = codegen::sql::mysql::generate(&root);
+  println!("{}", sql);
+
+  assert_eq!(sql, fs::read_to_string("tests/snapshots/20-codegen.mysql.sql").unwrap());
+}
+
+#[test]
+fn rust_codegen() {
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+  let options = codegen::rust::RustOptions { serde: true, ..Default::default() };
+  let code = codegen::rust::generate(&root, &options);
+  println!("{}", code);
+
+  assert_eq!(code, fs::read_to_string("tests/snapshots/20-codegen.rs").unwrap());
+
+  // the snapshot is compiled below, so the generated structs are checked by rustc too
+  let user: generated::User = serde_json::from_str(r#"{
+    "id": 1, "name": "ann", "email": "ann@example.com", "nicknames": null, "posts": [],
+    "profile": { "user": { "id": 1, "name": "ann", "email": "ann@example.com", "posts": [], "role": "ADMIN" } },
+    "role": "MEMBER"
+  }"#).unwrap();
+  assert!(matches!(user.role, generated::Role::Member));
+  assert!(matches!(user.profile.unwrap().user.role, generated::Role::Admin));
+}
+
+#[allow(dead_code)]
+mod generated {
+  include!("snapshots/20-codegen.rs");
+}
+
+#[test]
+fn typescript_codegen() {
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+  let files = codegen::typescript::generate(&root, &Default::default());
+
+  assert_eq!(files.len(), 1);
+  assert_eq!(files[0].path, "index.d.ts");
+  assert_eq!(files[0].contents, fs::read_to_string("tests/snapshots/20-codegen.d.ts").unwrap());
+
+  let options = codegen::typescript::TypeScriptOptions { nullability: codegen::typescript::Nullability::Null, file_per_model: true };
+  let files = codegen::typescript::generate(&root, &options);
+  let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+  assert_eq!(paths, vec!["User.d.ts", "Post.d.ts", "Profile.d.ts", "Group.d.ts", "Tag.d.ts", "Role.d.ts"]);
+
+  let post = &files[1].contents;
+  println!("{}", post);
+  assert!(post.starts_with("import type { Tag } from \"./Tag\";\nimport type { User } from \"./User\";\n\nexport interface Post {\n"));
+  assert!(post.contains("    tags: (Tag | null)[] | null;\n"));
+  assert!(!files[3].contents.contains("import type { Group }"), "no self import");
+}
+
+#[test]
+fn graphql_codegen() {
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+
+  for (query, golden) in [(false, "tests/snapshots/20-codegen.graphql"), (true, "tests/snapshots/20-codegen.query.graphql")] {
+    let options = codegen::graphql::GraphQlOptions { query, ..Default::default() };
+    let sdl = codegen::graphql::generate(&root, &options);
+    println!("{}", sdl);
+
+    assert_eq!(sdl, fs::read_to_string(golden).unwrap());
+    assert_valid_sdl(&sdl);
+  }
+}
+
+// parses the sdl and checks every referenced type is either built in or declared
+fn assert_valid_sdl(sdl: &str) {
+  use graphql_parser::schema::{self, Definition, TypeDefinition};
+
+  fn named<'a>(t: &'a schema::Type<'a, String>) -> &'a str {
+    match t {
+      schema::Type::NamedType(name) => name,
+      schema::Type::ListType(inner) | schema::Type::NonNullType(inner) => named(inner),
+    }
+  }
+
+  let document = graphql_parser::parse_schema::<String>(sdl).unwrap();
+  let mut declared = vec!["Int", "Float", "String", "Boolean", "ID"];
+  let mut referenced = Vec::new();
+
+  for definition in document.definitions.iter() {
+    match definition {
+      Definition::TypeDefinition(TypeDefinition::Object(o)) => {
+        declared.push(&o.name);
+        for field in o.fields.iter() {
+          referenced.push(named(&field.field_type));
+          referenced.extend(field.arguments.iter().map(|a| named(&a.value_type)));
+        }
+      },
+      Definition::TypeDefinition(TypeDefinition::Enum(e)) => declared.push(&e.name),
+      other => panic!("unexpected definition {:?}", other),
+    }
+  }
+
+  for name in referenced {
+    assert!(declared.contains(&name), "unknown type {}", name);
+  }
+}
+
+#[test]
+fn graphql_import() {
+  let sdl = fs::read_to_string("tests/graphql-files/1-import.graphql").unwrap();
+  let root = import::graphql::import(&sdl).unwrap();
+  println!("{}", root);
+
+  let names: Vec<&str> = root.types.iter().map(|m| m.name.as_str()).collect();
+  assert_eq!(names, vec!["Film", "Review"]);
+
+  let film = root.model("Film").unwrap();
+  assert_eq!(film.doc.as_deref(), Some("A film in the catalogue.\n\nShown on the home page."));
+  assert_eq!(film.annotations[0].to_string(), "@db(name: \"films\", tags: [\"public\", \"v2\"])");
+  assert_eq!(film.fields[0].to_string(), "id: String! @id");
+  assert_eq!(film.fields[1].to_string(), "title: String! @length(max: 120)");
+  assert_eq!(film.fields[1].doc.as_deref(), Some("the original title"));
+  assert_eq!(film.fields[3].type_string(), "[Genre!]!");
+  assert_eq!(film.fields[4].type_string(), "[Review]");
+  assert!(matches!(film.fields[4].field_type, ast::FieldType::Identfier(..)));
+
+  let body = &root.model("Review").unwrap().fields[2];
+  assert_eq!(body.annotations[0].arg("reason"), Some(&AnnotationValue::String(String::from("use \"text\" instead"))));
+  assert_eq!(root.enum_def("Genre").unwrap().variants[0].doc.as_deref(), Some("people laughing"));
+
+  let index = LineIndex::new(&sdl);
+  assert_eq!(index.line_col(film.fields[2].name_span.start).to_string(), "19:3");
+}
+
+#[test]
+fn graphql_export_import_round_trip() {
+  let sdl = fs::read_to_string("tests/snapshots/20-codegen.query.graphql").unwrap();
+  let root = import::graphql::import(&sdl).unwrap();
+  let options = codegen::graphql::GraphQlOptions { query: true, ..Default::default() };
+
+  assert_eq!(codegen::graphql::generate(&root, &options), sdl);
+}
+
+#[test]
+fn graphql_import_errors() {
+  let cases = vec![
+    ("interface Node { id: ID! }", "Interfaces can't be represented in odetto yet", "1:1"),
+    ("type A implements Node { id: ID! }", "Interfaces can't be represented in odetto yet", "1:8"),
+    ("type A { id: ID }\nunion U = A", "Unions can't be represented in odetto yet", "2:1"),
+    ("type A { ok: Boolean }", "The `Boolean` scalar can't be represented in odetto yet", "1:14"),
+    ("type A { id: Int @key(fields: {id: true}) }", "Objects as directive arguments can't be represented in odetto yet", "1:31"),
+    ("type A { id: Int @key(fields: null) }", "`null` as a directive argument can't be represented in odetto yet", "1:31"),
+    ("type A { b(first: Int): Int }", "Field arguments can't be represented in odetto yet", "1:11"),
+    ("type A { b: [[Int]] }", "Nested lists can't be represented in odetto yet", "1:14"),
+    ("type A { b: B }", "Type 'B' is not declared in the document", "1:13"),
+    ("type A { b: Int b: Int }", "Field 'b' is declared more than once on 'A'", "1:17"),
+    ("type A { b: Int }\ntype A { c: Int }", "Type 'A' is declared more than once", "2:6"),
+    ("type A { b Int }", "Expected `:`", "1:12"),
+    ("type A { b: \"Int\" }", "Expected a type", "1:13"),
+  ];
+
+  for (sdl, message, position) in cases {
+    let error: ImportError = import::graphql::import(sdl).unwrap_err();
+    assert_eq!(error.to_string(), message, "{}", sdl);
+    assert_eq!(LineIndex::new(sdl).line_col(error.span().start).to_string(), position, "{}", sdl);
+  }
+}
+
+#[test]
+fn formatter() {
+  let src = fs::read_to_string("tests/odet-files/21-format.odet").unwrap();
+  let tokens = lexer::Lexer::new(&src).run();
+  let root = parser::Parser::new(&tokens).parse().unwrap();
+  let formatted = format::format(&root, &tokens);
+  println!("{}", formatted);
+
+  assert_eq!(formatted, fs::read_to_string("tests/snapshots/21-format.odet").unwrap());
+
+  let tokens = lexer::Lexer::new(&formatted).run();
+  let reparsed = parser::Parser::new(&tokens).parse().unwrap();
+  assert_eq!(format::format(&reparsed, &tokens), formatted);
+  assert_eq!(trimmed_lines(&reparsed), trimmed_lines(&root));
+}
+
+#[test]
+fn formatter_is_stable_on_fixtures() {
+  for entry in fs::read_dir("tests/odet-files").unwrap() {
+    let path = entry.unwrap().path();
+    let src = fs::read_to_string(&path).unwrap();
+    let tokens = lexer::Lexer::new(&src).run();
+    let root = match parser::Parser::new(&tokens).parse() {
+      Ok(root) => root,
+      Err(_) => continue,
+    };
+
+    let formatted = format::format(&root, &tokens);
+    let tokens = lexer::Lexer::new(&formatted).run();
+    let reparsed = parser::Parser::new(&tokens).parse().unwrap();
+
+    assert_eq!(trimmed_lines(&reparsed), trimmed_lines(&root), "{:?}", path);
+    assert_eq!(format::format(&reparsed, &tokens), formatted, "{:?}", path);
+    assert_eq!(tokens.comments().len(), lexer::Lexer::new(&src).run().comments().len(), "{:?}", path);
+  }
+}
+
+// doc strings are re-indented by the formatter
+fn trimmed_lines(root: &ast::Root) -> Vec<String> {
+  root.to_string().lines().map(|l| l.trim().to_string()).collect()
+}
+
+#[test]
+fn formatter_prints_imported_schema() {
+  let sdl = fs::read_to_string("tests/graphql-files/1-import.graphql").unwrap();
+  let root = import::graphql::import(&sdl).unwrap();
+  let printed = format::print(&root);
+  println!("{}", printed);
+
+  let tokens = lexer::Lexer::new(&printed).run();
+  let reparsed = parser::Parser::new(&tokens).parse().unwrap();
+  assert_eq!(reparsed.to_string(), root.to_string());
+  assert!(printed.starts_with("\"\"\"\nA film in the catalogue.\n\nShown on the home page.\n\"\"\"\ntype Film @db(name: \"films\", tags: [\"public\", \"v2\"]) {\n    id: String! @id\n"));
+}
+
+#[test]
+fn lossless_syntax_tree() {
+  for entry in fs::read_dir("tests/odet-files").unwrap() {
+    let path = entry.unwrap().path();
+    let src = fs::read_to_string(&path).unwrap();
+    let tree = cst::parse(&src);
+
+    assert_eq!(tree.to_string(), src, "{:?}", path);
+  }
+
+  let src = fs::read_to_string("tests/odet-files/21-format.odet").unwrap();
+  let tree = cst::parse(&src);
+  let fields = tree.descendants(cst::NodeKind::Field);
+  let title = fields.iter().find(|f| f.token(lexer::TokenType::Identifier).unwrap().text == "title").unwrap();
+
+  assert_eq!(title.to_string(), "     title : String!  @length( max:120 )# shown everywhere\n");
+  assert_eq!(&src[title.span().start..title.span().end], "title : String!  @length( max:120 )");
+  assert_eq!(tree.descendants(cst::NodeKind::Model).len(), 3);
+  assert_eq!(tree.descendants(cst::NodeKind::Enum).len(), 1);
+}
+
+#[test]
+fn rename_model() {
+  let src = fs::read_to_string("tests/odet-files/21-format.odet").unwrap();
+  let edits = refactor::rename_type(&src, "Actor", "Performer").unwrap();
+  assert_eq!(edits.len(), 2);
+
+  let renamed = refactor::apply_edits(&src, &edits);
+  println!("{}", renamed);
+  assert_eq!(renamed, src.replace("Actor", "Performer"));
+  assert!(test_source(&renamed).model("Performer").is_some());
+
+  // enums share the namespace with models
+  let edits = refactor::rename_type(&src, "Genre", "Kind").unwrap();
+  let renamed = refactor::apply_edits(&src, &edits);
+  assert!(renamed.contains("genre : Kind!  @default( value : DRAMA )\n") && renamed.contains("enum Kind { COMEDY"));
+  assert_eq!(renamed.matches("Genre").count(), 0);
+
+  assert_eq!(refactor::rename_type(&src, "Film", "Film").unwrap(), vec![]);
+}
+
+#[test]
+fn rename_field() {
+  let src = fs::read_to_string("tests/odet-files/20-codegen.odet").unwrap();
+  let edits = refactor::rename_field(&src, "User", "posts", "articles").unwrap();
+  let renamed = refactor::apply_edits(&src, &edits);
+  assert!(renamed.contains("    articles: [Post!]! -> author\n") && renamed.contains("author: User! -> articles @index"));
+
+  let root = test_source(&renamed);
+  let author = ast::field(&root.model("Post").unwrap().fields, "author").unwrap();
+  assert_eq!(root.relation_inverse(author).unwrap().name, "articles");
+
+  // both sides of a self relation
+  let edits = refactor::rename_field(&src, "Group", "parent", "owner").unwrap();
+  let renamed = refactor::apply_edits(&src, &edits);
+  assert!(renamed.contains("owner: Group -> children") && renamed.contains("children: [Group] -> owner"));
+
+  // only relations pointing at the model are touched
+  let edits = refactor::rename_field(&src, "Profile", "user", "owner").unwrap();
+  assert_eq!(edits.len(), 2);
+  assert!(refactor::apply_edits(&src, &edits).contains("profile: Profile -> owner"));
+
+  // annotations on the model listing the field follow the rename
+  let src = "type Movie @index(fields: [\"year\", \"title\"]) @db(name: \"title\") { title: String year: Int }";
+  let edits = refactor::rename_field(src, "Movie", "title", "name").unwrap();
+  assert_eq!(refactor::apply_edits(src, &edits), "type Movie @index(fields: [\"year\", \"name\"]) @db(name: \"title\") { name: String year: Int }");
+}
+
+#[test]
+fn rename_errors() {
+  let src = fs::read_to_string("tests/odet-files/20-codegen.odet").unwrap();
+
+  match refactor::rename_type(&src, "Post", "Tag") {
+    Err(e @ RenameError::DuplicateTypeError(..)) => assert_eq!(&src[e.span().unwrap().start..e.span().unwrap().end], "Tag"),
+    r => panic!("{:?}", r),
+  }
+  assert!(matches!(refactor::rename_type(&src, "Post", "Role"), Err(RenameError::DuplicateTypeError(..))));
+  assert!(matches!(refactor::rename_type(&src, "Comment", "Note"), Err(RenameError::UnknownTypeError(_))));
+  assert!(matches!(refactor::rename_field(&src, "User", "email", "name"), Err(RenameError::DuplicateFieldError(..))));
+  assert!(matches!(refactor::rename_field(&src, "User", "mail", "address"), Err(RenameError::UnknownFieldError(..))));
+
+  for name in ["type", "Int", "two words", "", "@x", "9lives"].iter() {
+    assert!(matches!(refactor::rename_type(&src, "Post", name), Err(RenameError::InvalidNameError(_))), "{}", name);
+  }
+
+  match refactor::rename_type("type A { b: C }", "A", "B") {
+    Err(e @ RenameError::SchemaError(_)) => println!("{}", e),
+    r => panic!("{:?}", r),
+  }
+}
+
+#[test]
+fn rename_with_scalars() {
+  let src = "type Event {\n    at: DateTime!\n    title: String\n}\n";
+  let options = parser::ParseOptions { scalars: vec![String::from("DateTime")] };
+
+  let edits = refactor::rename_type_with_options(src, "Event", "Meeting", &options).unwrap();
+  assert_eq!(refactor::apply_edits(src, &edits), src.replace("Event", "Meeting"));
+  let edits = refactor::rename_field_with_options(src, "Event", "at", "starts_at", &options).unwrap();
+  assert_eq!(refactor::apply_edits(src, &edits), src.replace("at:", "starts_at:"));
+
+  assert!(matches!(refactor::rename_type_with_options(src, "Event", "DateTime", &options), Err(RenameError::ScalarNameError(_))));
+  assert!(matches!(refactor::rename_type(src, "Event", "Meeting"), Err(RenameError::SchemaError(_))));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_shape() {
+  let root = test_file("tests/odet-files/22-serde.odet").unwrap();
+  let json = serde_json::to_string_pretty(&root).unwrap() + "\n";
+  assert_eq!(json, fs::read_to_string("tests/snapshots/22-serde.json").unwrap());
+
+  let from_json: ast::Root = serde_json::from_str(&json).unwrap();
+  assert_eq!(from_json.to_string(), root.to_string());
+  assert_eq!(from_json.types[0].fields[2].field_type.span(), root.types[0].fields[2].field_type.span());
+
+  let yaml = serde_yaml::to_string(&root).unwrap();
+  let from_yaml: ast::Root = serde_yaml::from_str(&yaml).unwrap();
+  assert_eq!(from_yaml.to_string(), root.to_string());
+  assert!(yaml.contains("\n    type_type: required_array\n"));
+}
+
+// counts the models, the kind of in-house generator the registry is for
+struct ModelCount;
+
+impl codegen::Generator for ModelCount {
+  fn name(&self) -> &str {
+    "count"
+  }
+
+  fn generate(&self, root: &ast::Root, options: &codegen::GeneratorOptions) -> GenerateResult<Vec<codegen::GeneratedFile>> {
+    codegen::generator::check_options(self.name(), options, &["file"])?;
+    let path = options.get("file").cloned().unwrap_or_else(|| String::from("count.txt"));
+    Ok(codegen::generator::single_file(&path, format!("{}\n", root.types.len())))
+  }
+}
+
+#[test]
+fn generator_registry() {
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+  let mut registry = codegen::Registry::new();
+  assert_eq!(registry.names()[..6], ["postgres", "sqlite", "mysql", "rust", "typescript", "graphql"]);
+
+  let no_options = codegen::GeneratorOptions::new();
+  let files = registry.generate("postgres", &root, &no_options).unwrap();
+  assert_eq!(files, vec![codegen::GeneratedFile { path: String::from("schema.sql"), contents: codegen::sql::postgres::generate(&root) }]);
+
+  let mut options = codegen::GeneratorOptions::new();
+  options.insert(String::from("query"), String::from("true"));
+  let files = registry.generate("graphql", &root, &options).unwrap();
+  assert_eq!(files[0].contents, fs::read_to_string("tests/snapshots/20-codegen.query.graphql").unwrap());
+
+  let mut options = codegen::GeneratorOptions::new();
+  options.insert(String::from("nullability"), String::from("null"));
+  options.insert(String::from("file_per_model"), String::from("true"));
+  assert_eq!(registry.generate("typescript", &root, &options).unwrap().len(), 6);
+
+  registry.register(ModelCount);
+  let mut options = codegen::GeneratorOptions::new();
+  options.insert(String::from("file"), String::from("models.txt"));
+  let files = registry.generate("count", &root, &options).unwrap();
+  assert_eq!(files, vec![codegen::GeneratedFile { path: String::from("models.txt"), contents: String::from("5\n") }]);
+  assert_eq!(registry.names().last(), Some(&"count"));
+}
+
+#[test]
+fn generator_errors() {
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+  let registry = codegen::Registry::new();
+  let option = |key: &str, value: &str| {
+    let mut options = codegen::GeneratorOptions::new();
+    options.insert(key.to_string(), value.to_string());
+    options
+  };
+
+  assert!(matches!(registry.generate("cobol", &root, &codegen::GeneratorOptions::new()), Err(GenerateError::UnknownTargetError(_))));
+  assert!(matches!(registry.generate("postgres", &root, &option("serde", "true")), Err(GenerateError::UnknownOptionError(..))));
+  assert!(matches!(registry.generate("rust", &root, &option("serde", "yes")), Err(GenerateError::InvalidOptionError(..))));
+
+  match registry.generate("typescript", &root, &option("nullability", "maybe")) {
+    Err(e) => assert_eq!(e.to_string(), "Option 'nullability' expects optional or null, got 'maybe'"),
+    Ok(_) => panic!("invalid nullability was accepted"),
+  }
+  assert!(codegen::Registry::empty().names().is_empty());
+}
+
+#[cfg(all(unix, feature = "serde"))]
+#[test]
+fn external_generator() {
+  use codegen::{Generator, plugin::{ExternalGenerator, PluginRequest}};
+
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+  let mut options = codegen::GeneratorOptions::new();
+  options.insert(String::from("style"), String::from("short"));
+
+  let echo = ExternalGenerator::new("echo", "tests/plugins/odetto-gen-echo");
+  let response = echo.run(&root, &options).unwrap();
+  assert_eq!(response.files, vec![codegen::GeneratedFile { path: String::from("models/list.txt"), contents: String::from("User\nPost\n") }]);
+  assert!(!response.has_errors());
+
+  let diagnostics = response.diagnostics("echo");
+  assert_eq!(diagnostics[0].code, "echo");
+  assert!(!diagnostics[0].is_error());
+  assert_eq!(diagnostics[0].span, Some(root.enums[0].name_span));
+
+  // warnings don't stop a registered plugin, errors do
+  assert_eq!(echo.generate(&root, &options).unwrap().len(), 1);
+  let failing = ExternalGenerator::new("failing", "tests/plugins/odetto-gen-failing");
+  match failing.generate(&root, &options) {
+    Err(e) => assert_eq!(e.to_string(), "Generator 'failing' failed: User needs a primary key"),
+    Ok(_) => panic!("errors from the plugin were ignored"),
+  }
+
+  assert!(matches!(ExternalGenerator::new("broken", "tests/plugins/odetto-gen-broken").run(&root, &options), Err(GenerateError::GeneratorError(..))));
+  assert!(matches!(ExternalGenerator::new("missing", "tests/plugins/odetto-gen-missing").run(&root, &options), Err(GenerateError::GeneratorError(..))));
+  assert!(ExternalGenerator::find("../plugins/odetto-gen-echo").is_none());
+
+  // the request round trips, plugins written in rust can reuse the types
+  let request = PluginRequest { version: codegen::plugin::PROTOCOL_VERSION, target: String::from("echo"), options, schema: root.clone() };
+  let json = serde_json::to_string(&request).unwrap();
+  let parsed: PluginRequest = serde_json::from_str(&json).unwrap();
+  assert_eq!(parsed.schema.to_string(), root.to_string());
+  assert_eq!(parsed.options["style"], "short");
+}
+
+#[cfg(feature = "templates")]
+#[test]
+fn template_generator() {
+  use codegen::{Generator, template::{self, Template}};
+
+  let root = test_file("tests/odet-files/20-codegen.odet").unwrap();
+  let mut options = codegen::GeneratorOptions::new();
+  options.insert(String::from("dir"), String::from("tests/templates"));
+  options.insert(String::from("prefix"), String::from("enum-"));
+
+  let files = codegen::Registry::new().generate("template", &root, &options).unwrap();
+  let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+  assert_eq!(paths, vec![
+    "fixtures.json",
+    "repositories/user_repository.py",
+    "repositories/post_repository.py",
+    "repositories/profile_repository.py",
+    "repositories/group_repository.py",
+    "repositories/tag_repository.py",
+    "Role.txt",
+  ]);
+
+  let fixtures: serde_json::Value = serde_json::from_str(&files[0].contents).unwrap();
+  assert_eq!(fixtures["users"][0].as_object().unwrap().len(), 5);
+  assert!(files[1].contents.starts_with("# generated from the odetto schema, do not edit\n\nclass UserRepository:\n    table = \"users\"\n    \"\"\"people using the site\"\"\"\n"));
+  assert!(files[1].contents.contains("    def find(self, id, name, email):\n"));
+  assert!(files[1].contents.contains("        return []  # Post, never empty entries\n"));
+  assert!(files[3].contents.contains("    def find(self, bio=None):\n"));
+  assert_eq!(files[6].contents, "enum-Role: ADMIN | MEMBER\n");
+
+  let template = |path: &str, source: &str| vec![Template { path: path.to_string(), source: source.to_string() }];
+  let rendered = template::render(&root, &template("{{ model.name | camel_case }}.ts", "{{ model.fields | length }}"), &options).unwrap();
+  assert_eq!((rendered[3].path.as_str(), rendered[3].contents.as_str()), ("group.ts", "4"));
+
+  match template::render(&root, &template("a.txt", "{{ models | snake_case }}"), &options) {
+    Err(e) => assert!(e.to_string().contains("expected a name"), "{}", e),
+    Ok(_) => panic!("snake_case accepted a list"),
+  }
+  assert!(matches!(template::render(&root, &template("a.txt", "{{ missing.name }}"), &options), Err(GenerateError::GeneratorError(..))));
+  assert!(matches!(template::Templates.generate(&root, &codegen::GeneratorOptions::new()), Err(GenerateError::MissingOptionError(..))));
+  assert!(template::load(std::path::Path::new("tests/no-templates")).is_err());
+}
+
+#[test]
+fn custom_scalars() {
+  let src = "type Event { id: Int! at: DateTime! tags: [Uuid] }";
+  let tokens = lexer::Lexer::new(src).run();
+  assert!(parser::Parser::new(&tokens).parse().is_err());
+
+  let options = parser::ParseOptions { scalars: vec![String::from("DateTime"), String::from("Uuid")] };
+  let root = parser::Parser::with_options(&tokens, options).parse().unwrap();
+  assert!(matches!(&root.types[0].fields[1].field_type, ast::FieldType::Scalar(name, _) if name == "DateTime"));
+
+  let graphql = codegen::graphql::generate(&root, &Default::default());
+  assert!(graphql.starts_with("scalar DateTime\nscalar Uuid\n\ntype Event {\n"), "{}", graphql);
+  let typescript = codegen::typescript::generate(&root, &Default::default());
+  assert!(typescript[0].contents.contains("    at: string;\n    tags?: (string | null)[];\n"), "{}", typescript[0].contents);
+  let sql = codegen::Registry::new().generate("postgres", &root, &codegen::GeneratorOptions::new()).unwrap();
+  assert!(sql[0].contents.contains("    \"at\" TEXT NOT NULL,\n    \"tags\" TEXT[]\n"), "{}", sql[0].contents);
+
+  // keys of a custom scalar type are referenced with the same column type
+  let src = "type Event { id: Uuid! guests: [Guest] } type Guest { event: Event! }";
+  let tokens = lexer::Lexer::new(src).run();
+  let root = parser::Parser::with_options(&tokens, parser::ParseOptions { scalars: vec![String::from("Uuid")] }).parse().unwrap();
+  let sql = codegen::sql::postgres::generate(&root);
+  assert!(sql.contains("    \"id\" TEXT PRIMARY KEY\n") && sql.contains("    \"event_id\" TEXT NOT NULL"), "{}", sql);
+  assert!(sql.contains("CREATE TABLE \"event_guests\" (\n    \"event_id\" TEXT NOT NULL,\n"), "{}", sql);
+
+  // a type can't take the name of a custom scalar
+  let tokens = lexer::Lexer::new("type Event { id: Int! } enum DateTime { A }").run();
+  let options = parser::ParseOptions { scalars: vec![String::from("DateTime")] };
+  assert!(matches!(parser::Parser::with_options(&tokens, options).parse(), Err(ParseError::DuplicateModelIdentifierError(_))));
+
+  assert!(lexer::is_identifier("DateTime"));
+  assert!(!lexer::is_identifier("Int"));
+  assert!(!lexer::is_identifier("type"));
+  assert!(!lexer::is_identifier("Date Time"));
+}
+
+#[test]
+fn lint_rules() {
+  use odetto::lint::{self, Rule};
+
+  let root = test_source("type user { Name: String }\n\"\"\"doc\"\"\"\nenum Role { ADMIN }");
+  let codes = |rules: &[Rule]| lint::lint(&root, rules).iter().map(|d| d.code.clone()).collect::<Vec<_>>();
+
+  assert_eq!(codes(&Rule::ALL), vec!["missing-doc", "type-name-case", "missing-primary-key", "field-name-case"]);
+  assert_eq!(codes(&[Rule::FieldNameCase]), vec!["field-name-case"]);
+  assert!(codes(&[]).is_empty());
+
+  let diagnostics = lint::lint(&root, &[Rule::TypeNameCase]);
+  assert!(!diagnostics[0].is_error());
+  assert_eq!(diagnostics[0].help.as_deref(), Some("rename it to `User`"));
+  assert_eq!(Rule::from_name("missing-primary-key"), Some(Rule::MissingPrimaryKey));
+  assert_eq!(Rule::from_name("missing_doc"), None);
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn project_config() {
+  use odetto::{config, _helpers::ConfigError};
+
+  let yaml = config::load(std::path::Path::new("tests/projects/shop/odetto.yml")).unwrap();
+  assert_eq!(yaml.scalars, vec!["DateTime"]);
+  assert_eq!(yaml.lint_rules(), vec![odetto::lint::Rule::MissingDoc, odetto::lint::Rule::MissingPrimaryKey]);
+  assert_eq!(yaml.targets[1].options()["file_per_model"], "true");
+  assert_eq!(yaml.out_dir(&yaml.targets[0]), std::path::Path::new("tests/projects/shop/gen/sql"));
+
+  let toml = config::parse("odetto.toml", r#"
+schema = ["schema/*.odet"]
+scalars = ["DateTime"]
+lint = ["missing-doc", "missing-primary-key"]
+
+[[targets]]
+target = "postgres"
+out = "gen/sql"
+
+[[targets]]
+target = "typescript"
+out = "gen/ts"
+options = { file_per_model = true }
+"#).unwrap();
+  assert_eq!(config::Config { dir: yaml.dir.clone(), ..toml }, yaml);
+  assert_eq!(config::parse("odetto.yml", "").unwrap().schema, vec!["*.odet"]);
+
+  assert!(matches!(config::parse("odetto.yml", "lint: [everything]"), Err(ConfigError::UnknownLintError(_))));
+  assert!(matches!(config::parse("odetto.yml", "scalars: [Int]"), Err(ConfigError::InvalidScalarError(_))));
+  assert!(matches!(config::parse("odetto.yml", "target: postgres"), Err(ConfigError::SyntaxError(..))));
+  assert!(matches!(config::parse("odetto.toml", "[[targets]]\ntarget = \"rust\""), Err(ConfigError::SyntaxError(..))));
+  assert!(matches!(config::load(std::path::Path::new("tests/projects/missing.yml")), Err(ConfigError::ReadError(..))));
+  assert_eq!(config::find(std::path::Path::new("tests/projects/shop/schema")), Some(std::path::PathBuf::from("tests/projects/shop/odetto.yml")));
+
+  let files = yaml.schema_files().unwrap();
+  assert_eq!(files, vec![std::path::PathBuf::from("tests/projects/shop/schema/customers.odet"), std::path::PathBuf::from("tests/projects/shop/schema/orders.odet")]);
+  let missing = config::Config { schema: vec![String::from("schema/*.odet"), String::from("models/*.odet")], ..yaml.clone() };
+  assert!(matches!(missing.schema_files(), Err(ConfigError::NoSchemaError(p)) if p == "models/*.odet"));
+  let invalid = config::Config { schema: vec![String::from("schema/[.odet")], ..yaml.clone() };
+  assert!(matches!(invalid.schema_files(), Err(ConfigError::PatternError(..))));
+
+  let sources = yaml.read_schema().unwrap();
+  let tokens = lexer::Lexer::new(&sources.text).run();
+  let root = parser::Parser::with_options(&tokens, yaml.parse_options()).parse().unwrap();
+  assert_eq!(root.types.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), vec!["Customer", "Order"]);
+}
+
+#[test]
+fn sources_render() {
+  use odetto::config::Sources;
+
+  let mut sources = Sources::default();
+  sources.push("a.odet", "type A { b: B }");
+  sources.push("b.odet", "type B {\n  id: Int\n}\n");
+  assert_eq!(sources.text, "type A { b: B }\ntype B {\n  id: Int\n}\n");
+  assert_eq!(sources.file(20).unwrap().name, "b.odet");
+
+  let id = sources.text.find("id").unwrap();
+  let diagnostic = Diagnostic::warning("W", "here").with_span(odetto::span::Span::new(id, id + 2));
+  assert_eq!(sources.render(&diagnostic), "warning[W]: here\n --> b.odet:2:3\n  |\n2 |   id: Int\n  |   ^^\n");
+  assert_eq!(sources.render(&Diagnostic::error("E", "nowhere")), "error[E]: nowhere\n --> a.odet\n");
+}
+
+fn test_source(src: &str) -> ast::Root {
+  let tokens = lexer::Lexer::new(src).run();
+  parser::Parser::new(&tokens).parse().unwrap()
+}
+
 fn test_file(file_name: &str) -> Result<ast::Root, ParseError> {
     let start = Instant::now();
     let contents = fs::read_to_string(file_name).unwrap();
@@ -70,5 +1046,5 @@ fn test_file(file_name: &str) -> Result<ast::Root, ParseError> {
     let result = p.parse();
     println!("{} took {:?}", file_name, start.elapsed());
 
-    return result;
-}
\ No newline at end of file
+    result
+}